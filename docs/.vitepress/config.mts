@@ -3,7 +3,7 @@ import { defineConfig } from "vitepress";
 // https://vitepress.dev/reference/site-config
 export default defineConfig({
   title: "ASP CLI",
-  description: "Datenkonvertierungs-Tool für JSON, YAML, TOML und CSV",
+  description: "Datenkonvertierungs-Tool für JSON, YAML, TOML, CSV und XML",
   lang: "de-DE",
 
   themeConfig: {
@@ -33,9 +33,11 @@ export default defineConfig({
           text: "Datenformate",
           items: [
             { text: "JSON", link: "/formats/json" },
+            { text: "NDJSON", link: "/formats/ndjson" },
             { text: "YAML", link: "/formats/yaml" },
             { text: "TOML", link: "/formats/toml" },
             { text: "CSV", link: "/formats/csv" },
+            { text: "XML", link: "/formats/xml" },
             { text: "Vergleich", link: "/formats/comparison" },
           ],
         },
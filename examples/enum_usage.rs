@@ -49,16 +49,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let format = FileFormat::Json;
     match format {
         FileFormat::Json => println!("\nWir arbeiten mit JSON!"),
+        FileFormat::Ndjson => println!("\nWir arbeiten mit JSON Lines!"),
         FileFormat::Yaml => println!("\nWir arbeiten mit YAML!"),
         FileFormat::Toml => println!("\nWir arbeiten mit TOML!"),
-        FileFormat::Csv => println!("\nWir arbeiten mit CSV!"),
+        FileFormat::Csv | FileFormat::Tsv | FileFormat::Psv => println!("\nWir arbeiten mit CSV!"),
+        FileFormat::Xml => println!("\nWir arbeiten mit XML!"),
     }
     
     // ============================================================================
     // Beispiel 5: Runtime Format-Auswahl
     // ============================================================================
     
-    let formats = vec!["json", "yaml", "toml", "csv"];
+    let formats = vec!["json", "yaml", "toml", "csv", "tsv", "xml"];
     
     for fmt_str in formats {
         let fmt = FileFormat::from_str(fmt_str)?;
@@ -6,29 +6,129 @@ use wasm_bindgen::prelude::*;
 // javascript type casting
 use wasm_bindgen::JsCast;
 // Rust bindings für alle web api functions
-use web_sys::{console, HtmlSelectElement, HtmlTextAreaElement, HtmlButtonElement};
+use web_sys::{console, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement, HtmlButtonElement};
 
 // Importiere das elegante FileFormat Enum
-use asp_cli::FileFormat;
-use std::str::FromStr;
+use asp_cli::{convert_reporting, ConversionWarning, ConvertOptions, FormatError, FormatRegistry, NullPolicy};
+use asp_cli::formats::csv::{CsvOptions, FlattenOptions, LineTerminator};
+use asp_cli::formats::json::JsonOptions;
+use asp_cli::formats::yaml::YamlOptions;
+use asp_cli::document::Span;
+use asp_cli::detect::detect_format;
 
 fn main() {
     // jede rust binary muss eine main funktion haben (Compiler requirement)
     // Für WASM wird die start() Funktion automatisch aufgerufen
 }
 
-// Elegante Konvertierung mit FileFormat Enum (viel sauberer!)
-fn perform_conversion(input_text: &str, input_format: &str, output_format: &str) -> Result<String, String> {
-    // Parse Format-Strings zu Enum
-    let input_fmt = FileFormat::from_str(input_format)
-        .map_err(|e| format!("Ungültiges Input-Format: {}", e))?;
+// Konvertierung über die FormatRegistry
+// Gibt zusätzlich den Namen des tatsächlich verwendeten Input-Formats zurück (relevant bei "auto")
+// und die Warnungen des Writers (z.B. ausgelassene null-Werte)
+fn perform_conversion(registry: &FormatRegistry, input_text: &str, input_format: &str, output_format: &str, options: &ConvertOptions) -> Result<(String, String, Vec<ConversionWarning>), FormatError> {
+    // Format-Strings über die Registry auflösen, "auto" erkennt das Format am Inhalt
+    let input_fmt = if input_format.eq_ignore_ascii_case("auto") {
+        let detected = detect_format(input_text, None).ok_or_else(|| {
+            FormatError::InvalidFormat("Input-Format konnte nicht erkannt werden".to_string())
+        })?;
+        registry.resolve(detected.as_str())?
+    } else {
+        registry.resolve(input_format)?
+    };
     
-    let output_fmt = FileFormat::from_str(output_format)
-        .map_err(|e| format!("Ungültiges Output-Format: {}", e))?;
+    let output_fmt = registry.resolve(output_format)?;
     
-    // Konvertierung durchführen - nur EINE Zeile! kommt von der format.rs file
-    input_fmt.convert(input_text, output_fmt)
-        .map_err(|e| e.to_string())
+    // Konvertierung durchführen: Lesen → Zwischenmodell → Schreiben
+    let (output, warnings) = convert_reporting(input_fmt, output_fmt, input_text, options)?;
+    Ok((output, input_fmt.name().to_string(), warnings))
+}
+
+// Liest die CSV-Optionen aus der Options-Leiste
+struct CsvControls {
+    delimiter: HtmlSelectElement,
+    quote: HtmlSelectElement,
+    escape: HtmlSelectElement,
+    no_double_quote: HtmlInputElement,
+    comment: HtmlSelectElement,
+    header: HtmlInputElement,
+    flexible: HtmlInputElement,
+    crlf: HtmlInputElement,
+    flatten_separator: HtmlSelectElement,
+    flatten_arrays: HtmlSelectElement,
+}
+
+impl CsvControls {
+    fn options(&self) -> CsvOptions {
+        let delimiter = match self.delimiter.value().as_str() {
+            "auto" => None,
+            "tab" => Some(b'\t'),
+            other => other.bytes().next(),
+        };
+        CsvOptions {
+            delimiter,
+            quote: self.quote.value().bytes().next().unwrap_or(b'"'),
+            // Leerer Wert = kein Zeichen
+            escape: self.escape.value().bytes().next(),
+            double_quote: !self.no_double_quote.checked(),
+            comment: self.comment.value().bytes().next(),
+            flexible: self.flexible.checked(),
+            has_headers: self.header.checked(),
+            terminator: if self.crlf.checked() { LineTerminator::Crlf } else { LineTerminator::Lf },
+            flatten: FlattenOptions {
+                separator: self.flatten_separator.value(),
+                arrays: self.flatten_arrays.value().parse().unwrap_or_default(),
+                ..FlattenOptions::default()
+            },
+            ..CsvOptions::default()
+        }
+    }
+}
+
+// Liest die null-Behandlung für TOML aus der Options-Leiste
+fn null_policy(policy: &HtmlSelectElement, sentinel: &HtmlInputElement) -> NullPolicy {
+    match policy.value().as_str() {
+        "empty" => NullPolicy::EmptyString,
+        "sentinel" => NullPolicy::Sentinel(sentinel.value()),
+        "error" => NullPolicy::Error,
+        _ => NullPolicy::Omit,
+    }
+}
+
+// Zeigt die Warnungen als Liste unter den Textfeldern (ohne Warnungen ausgeblendet)
+fn show_warnings(panel: &web_sys::Element, warnings: &[ConversionWarning]) {
+    let items: String = warnings
+        .iter()
+        .map(|w| format!("<li>[{}] {} <code>{}</code></li>", w.kind.as_str(), escape_html(&w.message), escape_html(&w.path)))
+        .collect();
+    panel.set_inner_html(&format!("<ul>{}</ul>", items));
+    if warnings.is_empty() {
+        let _ = panel.set_attribute("hidden", "");
+    } else {
+        let _ = panel.remove_attribute("hidden");
+    }
+}
+
+// Keys aus der Eingabe landen im Pfad - vor dem Einfügen als HTML escapen
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+// Markiert die fehlerhafte Zeile im Input-Textfeld
+// (Selection-Offsets im Browser sind UTF-16 Code Units, keine Bytes)
+fn highlight_error_line(textarea: &HtmlTextAreaElement, input_text: &str, span: Span) {
+    let line_start = input_text[..span.offset.min(input_text.len())]
+        .rfind('\n')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let line_end = input_text[line_start..]
+        .find('\n')
+        .map(|i| line_start + i)
+        .unwrap_or(input_text.len());
+    
+    let start = input_text[..line_start].encode_utf16().count() as u32;
+    let end = input_text[..line_end].encode_utf16().count() as u32;
+    
+    let _ = textarea.focus();
+    let _ = textarea.set_selection_range(start, end);
 }
 
 // hier startet das webassembly
@@ -78,6 +178,93 @@ pub fn start() -> Result<(), JsValue> {
     let status_div = document
         .get_element_by_id("status")
         .expect("status div nicht gefunden");
+    
+    let csv_controls = CsvControls {
+        delimiter: document
+            .get_element_by_id("csv-delimiter")
+            .expect("csv-delimiter select nicht gefunden")
+            .dyn_into::<HtmlSelectElement>()?,
+        quote: document
+            .get_element_by_id("csv-quote")
+            .expect("csv-quote select nicht gefunden")
+            .dyn_into::<HtmlSelectElement>()?,
+        escape: document
+            .get_element_by_id("csv-escape")
+            .expect("csv-escape select nicht gefunden")
+            .dyn_into::<HtmlSelectElement>()?,
+        no_double_quote: document
+            .get_element_by_id("csv-no-double-quote")
+            .expect("csv-no-double-quote checkbox nicht gefunden")
+            .dyn_into::<HtmlInputElement>()?,
+        comment: document
+            .get_element_by_id("csv-comment")
+            .expect("csv-comment select nicht gefunden")
+            .dyn_into::<HtmlSelectElement>()?,
+        header: document
+            .get_element_by_id("csv-header")
+            .expect("csv-header checkbox nicht gefunden")
+            .dyn_into::<HtmlInputElement>()?,
+        flexible: document
+            .get_element_by_id("csv-flexible")
+            .expect("csv-flexible checkbox nicht gefunden")
+            .dyn_into::<HtmlInputElement>()?,
+        crlf: document
+            .get_element_by_id("csv-crlf")
+            .expect("csv-crlf checkbox nicht gefunden")
+            .dyn_into::<HtmlInputElement>()?,
+        flatten_separator: document
+            .get_element_by_id("csv-flatten-separator")
+            .expect("csv-flatten-separator select nicht gefunden")
+            .dyn_into::<HtmlSelectElement>()?,
+        flatten_arrays: document
+            .get_element_by_id("csv-flatten-arrays")
+            .expect("csv-flatten-arrays select nicht gefunden")
+            .dyn_into::<HtmlSelectElement>()?,
+    };
+
+    let null_policy_select = document
+        .get_element_by_id("null-policy")
+        .expect("null-policy select nicht gefunden")
+        .dyn_into::<HtmlSelectElement>()?;
+    
+    let null_sentinel_input = document
+        .get_element_by_id("null-sentinel")
+        .expect("null-sentinel input nicht gefunden")
+        .dyn_into::<HtmlInputElement>()?;
+
+    let strict_checkbox = document
+        .get_element_by_id("strict")
+        .expect("strict checkbox nicht gefunden")
+        .dyn_into::<HtmlInputElement>()?;
+    
+    let sort_keys_checkbox = document
+        .get_element_by_id("sort-keys")
+        .expect("sort-keys checkbox nicht gefunden")
+        .dyn_into::<HtmlInputElement>()?;
+    
+    let big_numbers_select = document
+        .get_element_by_id("big-numbers")
+        .expect("big-numbers select nicht gefunden")
+        .dyn_into::<HtmlSelectElement>()?;
+    
+    let json_datetime_select = document
+        .get_element_by_id("json-datetime")
+        .expect("json-datetime select nicht gefunden")
+        .dyn_into::<HtmlSelectElement>()?;
+    
+    let multi_document_checkbox = document
+        .get_element_by_id("yaml-multi-document")
+        .expect("yaml-multi-document checkbox nicht gefunden")
+        .dyn_into::<HtmlInputElement>()?;
+    
+    let keep_aliases_checkbox = document
+        .get_element_by_id("yaml-keep-aliases")
+        .expect("yaml-keep-aliases checkbox nicht gefunden")
+        .dyn_into::<HtmlInputElement>()?;
+    
+    let warnings_div = document
+        .get_element_by_id("warnings")
+        .expect("warnings div nicht gefunden");
 
     // Convert Button Event
     let input_ta = input_textarea.clone();
@@ -85,6 +272,10 @@ pub fn start() -> Result<(), JsValue> {
     let input_fmt = input_format.clone();
     let output_fmt = output_format.clone();
     let status = status_div.clone();
+    let warnings_panel = warnings_div.clone();
+    
+    // Registry mit allen eingebauten Formaten, lebt so lange wie die Closure
+    let registry = FormatRegistry::default();
     
     // javascript closure, um die event handlers zu registrieren
     let convert_closure = Closure::wrap(Box::new(move || { // move nimmt ownership der variablen und gibt sie an die closure funktion
@@ -97,15 +288,44 @@ pub fn start() -> Result<(), JsValue> {
             return;
         }
         
+        let options = ConvertOptions {
+            csv: csv_controls.options(),
+            json: JsonOptions { datetime: json_datetime_select.value().parse().unwrap_or_default() },
+            yaml: YamlOptions {
+                multi_document: multi_document_checkbox.checked(),
+                keep_aliases: keep_aliases_checkbox.checked(),
+                ..YamlOptions::default()
+            },
+            null_policy: null_policy(&null_policy_select, &null_sentinel_input),
+            number_policy: big_numbers_select.value().parse().unwrap_or_default(),
+            strict: strict_checkbox.checked(),
+            sort_keys: sort_keys_checkbox.checked(),
+            ..ConvertOptions::default()
+        };
+        
         // Echte Konvertierung durchführen, kommt von der web.rs file
-        match perform_conversion(&input_text, &input_format_val, &output_format_val) {
-            Ok(output_text) => {
+        match perform_conversion(&registry, &input_text, &input_format_val, &output_format_val, &options) {
+            Ok((output_text, used_format, warnings)) => {
                 output_ta.set_value(&output_text); // zeigt ergebnis an im output textarea
-                status.set_inner_html(&format!("success: {} → {} conversion complete", input_format_val.to_lowercase(), output_format_val.to_lowercase()));
+                let mut message = format!("success: {} → {} conversion complete", used_format, output_format_val.to_lowercase());
+                if !warnings.is_empty() {
+                    message.push_str(&format!(" ({} warning(s))", warnings.len()));
+                }
+                status.set_inner_html(&message);
+                show_warnings(&warnings_panel, &warnings);
             }
             Err(error) => {
-                output_ta.set_value(&format!("# CONVERSION ERROR\n\n{}", error));
-                status.set_inner_html(&format!("error: {}", error));
+                // Parse-Fehler: Ausschnitt anzeigen und Zeile im Input markieren
+                let snippet = match &error {
+                    FormatError::ParseError { snippet: Some(snippet), .. } => format!("\n\n{}", snippet),
+                    _ => String::new(),
+                };
+                if let Some(span) = error.span() {
+                    highlight_error_line(&input_ta, &input_text, span);
+                }
+                output_ta.set_value(&format!("# CONVERSION ERROR\n\n{}{}", error, snippet));
+                show_warnings(&warnings_panel, &[]);
+                status.set_inner_html(&format!("error: {}", escape_html(&error.to_string())));
             }
         }
     }) as Box<dyn FnMut()>);
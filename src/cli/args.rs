@@ -0,0 +1,190 @@
+// Gemeinsame Kommandozeilen-Optionen für convert und validate
+
+use clap::Args;
+
+use crate::formats::csv::{ArrayMode, CsvOptions, FlattenOptions, LineTerminator};
+use crate::formats::json::{JsonDateTime, JsonOptions};
+use crate::formats::toml::{TomlOptions, DEFAULT_ROOT_KEY};
+use crate::formats::yaml::{YamlOptions, DEFAULT_MAX_ALIAS_NODES};
+use crate::options::{ConvertOptions, NullPolicy, NumberPolicy};
+
+/// CSV-Dialekt (gilt für CSV-Eingabe und -Ausgabe)
+#[derive(Args, Debug, Clone, Default)]
+#[command(next_help_heading = "CSV-Optionen")]
+pub struct CsvArgs {
+    /// Trennzeichen, z.B. ";" oder "tab" (Standard: beim Lesen erkennen, sonst ",")
+    #[arg(long, value_name = "CHAR", value_parser = parse_byte)]
+    pub delimiter: Option<u8>,
+
+    /// Anführungszeichen für Felder [Standard: "]
+    #[arg(long, value_name = "CHAR", value_parser = parse_byte)]
+    pub quote: Option<u8>,
+
+    /// Escape-Zeichen für Anführungszeichen, z.B. "\"
+    #[arg(long, value_name = "CHAR", value_parser = parse_byte)]
+    pub escape: Option<u8>,
+
+    /// Anführungszeichen nicht verdoppeln (nur mit --escape sinnvoll)
+    #[arg(long)]
+    pub no_double_quote: bool,
+
+    /// Zeilen mit diesem Anfangszeichen ignorieren, z.B. "#"
+    #[arg(long, value_name = "CHAR", value_parser = parse_byte)]
+    pub comment: Option<u8>,
+
+    /// Zeilen mit unterschiedlich vielen Feldern erlauben
+    #[arg(long)]
+    pub flexible: bool,
+
+    /// Keine Header-Zeile (Zeilen werden zu Arrays)
+    #[arg(long)]
+    pub no_header: bool,
+
+    /// Zeilen mit \r\n statt \n beenden
+    #[arg(long)]
+    pub crlf: bool,
+
+    /// Spalten beim Schreiben festlegen, z.B. "id,name" (Auswahl und Reihenfolge)
+    #[arg(long, value_name = "A,B,...", value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// Trennzeichen für Spalten aus verschachtelten Keys, z.B. "." oder "/"
+    #[arg(long, value_name = "SEP", default_value = "_")]
+    pub flatten_separator: String,
+
+    /// Arrays beim Schreiben: json, index (tags_0), join, join:<TEXT> oder explode (eine Zeile pro Eintrag)
+    #[arg(long, value_name = "MODE", default_value = "json", value_parser = parse_array_mode)]
+    pub flatten_arrays: ArrayMode,
+
+    /// Höchstens so viele Keys pro Spaltenname, tiefere Werte als JSON-String
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub flatten_depth: Option<u32>,
+}
+
+impl CsvArgs {
+    pub fn to_options(&self) -> CsvOptions {
+        let defaults = CsvOptions::default();
+        CsvOptions {
+            delimiter: self.delimiter,
+            quote: self.quote.unwrap_or(defaults.quote),
+            escape: self.escape,
+            double_quote: !self.no_double_quote,
+            comment: self.comment,
+            flexible: self.flexible,
+            has_headers: !self.no_header,
+            terminator: if self.crlf { LineTerminator::Crlf } else { LineTerminator::Lf },
+            columns: (!self.columns.is_empty()).then(|| self.columns.clone()),
+            flatten: FlattenOptions {
+                separator: self.flatten_separator.clone(),
+                arrays: self.flatten_arrays.clone(),
+                max_depth: self.flatten_depth.map(|depth| depth as usize),
+            },
+        }
+    }
+}
+
+/// YAML-Eingabe und -Ausgabe
+#[derive(Args, Debug, Clone)]
+#[command(next_help_heading = "YAML-Optionen")]
+pub struct YamlArgs {
+    /// Root-Array als YAML-Stream mit einem Dokument pro Eintrag (---) schreiben
+    #[arg(long)]
+    pub yaml_multi_document: bool,
+
+    /// Merge-Keys (<<) bei YAML → YAML nicht auflösen
+    #[arg(long)]
+    pub yaml_keep_aliases: bool,
+
+    /// Maximale Anzahl Knoten, die Aliase erzeugen dürfen (Schutz vor Alias-Bomben)
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_ALIAS_NODES)]
+    pub yaml_max_alias_nodes: usize,
+}
+
+impl YamlArgs {
+    pub fn to_options(&self) -> YamlOptions {
+        YamlOptions {
+            multi_document: self.yaml_multi_document,
+            keep_aliases: self.yaml_keep_aliases,
+            max_alias_nodes: self.yaml_max_alias_nodes,
+        }
+    }
+}
+
+/// Verhalten der Writer bei Daten, die das Zielformat nicht exakt abbilden kann
+#[derive(Args, Debug, Clone)]
+#[command(next_help_heading = "Ausgabe-Optionen")]
+pub struct WriteArgs {
+    /// null in TOML: omit (Key weglassen + Warnung), empty, error oder sentinel:<TEXT>
+    #[arg(long = "null", value_name = "POLICY", default_value = "omit", value_parser = parse_null_policy)]
+    pub null_policy: NullPolicy,
+
+    /// Zahlen, die TOML nicht exakt kennt (> i64, mehr Stellen als f64): stringify, error oder clamp (mit Warnung)
+    #[arg(long, value_name = "POLICY", default_value = "stringify", value_parser = parse_number_policy)]
+    pub big_numbers: NumberPolicy,
+
+    /// Key für Root-Arrays in TOML, z.B. "items" → [[items]]
+    #[arg(long, value_name = "KEY", default_value = DEFAULT_ROOT_KEY)]
+    pub root_key: String,
+
+    /// Dateinamen der Eingabe als Key für Root-Arrays in TOML verwenden (users.json → [[users]])
+    #[arg(long, conflicts_with = "root_key")]
+    pub root_key_from_input: bool,
+
+    /// Datetimes in JSON: iso (String), epoch (Sekunden seit 1970) oder tagged ({"$datetime": ...})
+    #[arg(long, value_name = "STYLE", default_value = "iso", value_parser = parse_json_datetime)]
+    pub json_datetime: JsonDateTime,
+
+    /// Keys alphabetisch sortieren (Standard: Reihenfolge der Quelle, auch für CSV-Spalten)
+    #[arg(long)]
+    pub sort_keys: bool,
+
+    /// Jede Warnung (z.B. ausgelassene null-Werte, verlorene Tags) als Fehler behandeln
+    #[arg(long)]
+    pub strict: bool,
+}
+
+/// Alle Optionen für Reader und Writer aus den Kommandozeilen-Argumenten
+pub fn convert_options(csv: &CsvArgs, yaml: &YamlArgs, write: &WriteArgs) -> ConvertOptions {
+    ConvertOptions {
+        csv: csv.to_options(),
+        json: JsonOptions { datetime: write.json_datetime },
+        toml: TomlOptions {
+            root_key: write.root_key.clone(),
+            root_key_from_input: write.root_key_from_input,
+        },
+        yaml: yaml.to_options(),
+        null_policy: write.null_policy.clone(),
+        number_policy: write.big_numbers,
+        strict: write.strict,
+        sort_keys: write.sort_keys,
+    }
+}
+
+fn parse_null_policy(value: &str) -> Result<NullPolicy, String> {
+    value.parse().map_err(|e: crate::error::FormatError| e.to_string())
+}
+
+fn parse_number_policy(value: &str) -> Result<NumberPolicy, String> {
+    value.parse().map_err(|e: crate::error::FormatError| e.to_string())
+}
+
+fn parse_array_mode(value: &str) -> Result<ArrayMode, String> {
+    value.parse().map_err(|e: crate::error::FormatError| e.to_string())
+}
+
+fn parse_json_datetime(value: &str) -> Result<JsonDateTime, String> {
+    value.parse().map_err(|e: crate::error::FormatError| e.to_string())
+}
+
+/// Ein einzelnes ASCII-Zeichen; "tab" und "\t" stehen für den Tabulator
+fn parse_byte(value: &str) -> Result<u8, String> {
+    match value {
+        "tab" | "\\t" => return Ok(b'\t'),
+        "space" => return Ok(b' '),
+        _ => {}
+    }
+    match value.as_bytes() {
+        [byte] if byte.is_ascii() => Ok(*byte),
+        _ => Err(format!("erwartet genau ein ASCII-Zeichen, gefunden: {:?}", value)),
+    }
+}
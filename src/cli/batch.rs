@@ -0,0 +1,306 @@
+// Batch-Konvertierung - viele Dateien per Glob-Muster in einen Ausgabeordner
+//
+// Die Ordnerstruktur unterhalb des festen Teils des Musters wird im
+// Ausgabeordner nachgebaut. Die Formate kommen aus der FormatRegistry, pro
+// Datei wird convert_reporting aufgerufen, die Dateien werden parallel auf
+// mehrere Threads verteilt.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::cli::resolve_input_format;
+use crate::error::FormatError;
+use crate::options::ConvertOptions;
+use crate::registry::{Format, FormatRegistry};
+use crate::warning::{convert_reporting, ConversionWarning};
+
+/// Ergebnis für eine Datei
+pub enum BatchStatus {
+    /// Neu geschrieben
+    Converted,
+    /// Ausgabe ist neuer als die Eingabe
+    Skipped,
+    Failed(FormatError),
+}
+
+pub struct BatchEntry {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub status: BatchStatus,
+    /// Verlustbehaftete Stellen der Konvertierung (leer bei übersprungenen Dateien)
+    pub warnings: Vec<ConversionWarning>,
+}
+
+/// Konvertiert alle Dateien, die auf das Muster passen, nach `out_dir`
+///
+/// Ein Ordner als Muster steht für alle Dateien mit bekannter Endung darin
+/// (rekursiv). Ohne `force` werden Dateien übersprungen, deren Ausgabe
+/// neuer ist als die Eingabe.
+pub fn convert_batch(
+    registry: &FormatRegistry,
+    pattern: &str,
+    output_format: &dyn Format,
+    out_dir: &Path,
+    force: bool,
+    options: &ConvertOptions,
+) -> Result<Vec<BatchEntry>, FormatError> {
+    let inputs = collect_inputs(registry, pattern)?;
+    let base = glob_base(pattern);
+    let extension = output_format.extensions().first().copied().unwrap_or(output_format.name());
+
+    let jobs: Vec<(PathBuf, PathBuf)> = inputs
+        .into_iter()
+        .map(|input| {
+            let relative = input.strip_prefix(&base).unwrap_or(&input).to_path_buf();
+            let output = out_dir.join(relative).with_extension(extension);
+            (input, output)
+        })
+        .collect();
+
+    // Worker holen sich die nächste Datei über einen gemeinsamen Zähler
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(jobs.len())
+        .max(1);
+    let next = AtomicUsize::new(0);
+
+    let mut entries: Vec<(usize, BatchEntry)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some((input, output)) = jobs.get(index) else {
+                            break;
+                        };
+                        let (status, warnings) = convert_one(registry, input, output, output_format, force, options);
+                        let entry = BatchEntry { input: input.clone(), output: output.clone(), status, warnings };
+                        done.push((index, entry));
+                    }
+                    done
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Worker-Thread abgebrochen"))
+            .collect()
+    });
+
+    // Reihenfolge wie beim Expandieren des Musters
+    entries.sort_by_key(|(index, _)| *index);
+    Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+}
+
+/// Konvertiert eine Datei, falls die Ausgabe fehlt oder veraltet ist
+fn convert_one(
+    registry: &FormatRegistry,
+    input: &Path,
+    output: &Path,
+    output_format: &dyn Format,
+    force: bool,
+    options: &ConvertOptions,
+) -> (BatchStatus, Vec<ConversionWarning>) {
+    if !force && is_up_to_date(input, output) {
+        return (BatchStatus::Skipped, Vec::new());
+    }
+
+    let result = (|| {
+        let content = fs::read_to_string(input)
+            .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input.display(), e)))?;
+
+        let input_format = resolve_input_format(registry, &input.to_string_lossy(), None, &content)?;
+
+        let options = options.for_input(input);
+        let (result, warnings) = convert_reporting(input_format, output_format, &content, &options)?;
+
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                FormatError::IoError(format!("Fehler beim Anlegen von {}: {}", parent.display(), e))
+            })?;
+        }
+        fs::write(output, result)
+            .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output.display(), e)))?;
+        Ok(warnings)
+    })();
+
+    match result {
+        Ok(warnings) => (BatchStatus::Converted, warnings),
+        Err(e) => (BatchStatus::Failed(e), Vec::new()),
+    }
+}
+
+/// Ausgabe existiert und ist mindestens so neu wie die Eingabe
+fn is_up_to_date(input: &Path, output: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(input), modified(output)) {
+        (Some(input), Some(output)) => output >= input,
+        _ => false,
+    }
+}
+
+/// Alle Eingabedateien zum Muster (Glob, Ordner oder einzelne Datei)
+fn collect_inputs(registry: &FormatRegistry, pattern: &str) -> Result<Vec<PathBuf>, FormatError> {
+    let path = Path::new(pattern);
+    if path.is_dir() {
+        let all = path.join("**").join("*");
+        return Ok(expand_glob(&all.to_string_lossy())?
+            .into_iter()
+            .filter(|p| registry.for_path(p).is_some())
+            .collect());
+    }
+    if !is_glob(pattern) {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let inputs = expand_glob(pattern)?;
+    if inputs.is_empty() {
+        return Err(FormatError::IoError(format!("Keine Dateien gefunden für {}", pattern)));
+    }
+    Ok(inputs)
+}
+
+fn expand_glob(pattern: &str) -> Result<Vec<PathBuf>, FormatError> {
+    let entries = glob::glob(pattern)
+        .map_err(|e| FormatError::InvalidFormat(format!("Ungültiges Muster {}: {}", pattern, e)))?;
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| FormatError::IoError(e.to_string()))?;
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Fester Teil des Musters, z.B. "configs" bei "configs/**/*.yaml" -
+/// darunter wird die Struktur im Ausgabeordner nachgebaut
+fn glob_base(pattern: &str) -> PathBuf {
+    let path = Path::new(pattern);
+    if path.is_dir() {
+        return path.to_path_buf();
+    }
+
+    let mut base = PathBuf::new();
+    for component in path.components() {
+        if let Component::Normal(part) = component
+            && is_glob(&part.to_string_lossy())
+        {
+            return base;
+        }
+        base.push(component);
+    }
+    // Kein Platzhalter: einzelne Datei, Basis ist ihr Ordner
+    path.parent().map(Path::to_path_buf).unwrap_or_default()
+}
+
+// ============================================================================
+// ZUSAMMENFASSUNG
+// ============================================================================
+
+/// Tabelle mit einer Zeile pro Datei und einer Summenzeile
+///
+/// ```text
+/// Status       Eingabe             Ausgabe
+/// ✓ ok         configs/app.yaml    build/app.toml
+/// = aktuell    configs/db.yaml     build/db.toml
+/// ✗ Fehler     configs/bad.yaml    Parse-Fehler: ...
+///
+/// 3 Dateien: 1 konvertiert, 1 übersprungen, 1 fehlgeschlagen
+/// ```
+///
+/// Gibt es Warnungen, wird ihre Anzahl an die Summenzeile angehängt.
+pub fn render_summary(entries: &[BatchEntry]) -> String {
+    let rows: Vec<[String; 3]> = entries
+        .iter()
+        .map(|entry| {
+            let (status, target) = match &entry.status {
+                BatchStatus::Converted => ("✓ ok", entry.output.display().to_string()),
+                BatchStatus::Skipped => ("= aktuell", entry.output.display().to_string()),
+                BatchStatus::Failed(e) => ("✗ Fehler", e.to_string()),
+            };
+            [status.to_string(), entry.input.display().to_string(), target]
+        })
+        .collect();
+
+    let header = ["Status".to_string(), "Eingabe".to_string(), "Ausgabe".to_string()];
+    let width = |col: usize| {
+        std::iter::once(&header)
+            .chain(&rows)
+            .map(|row| row[col].chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+    let (status_width, input_width) = (width(0), width(1));
+
+    let mut out = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        out.push_str(&format!(
+            "{:<sw$}  {:<iw$}  {}\n",
+            row[0],
+            row[1],
+            row[2],
+            sw = status_width,
+            iw = input_width
+        ));
+    }
+
+    let count = |f: fn(&BatchStatus) -> bool| entries.iter().filter(|e| f(&e.status)).count();
+    out.push_str(&format!(
+        "\n{} Dateien: {} konvertiert, {} übersprungen, {} fehlgeschlagen",
+        entries.len(),
+        count(|s| matches!(s, BatchStatus::Converted)),
+        count(|s| matches!(s, BatchStatus::Skipped)),
+        count(|s| matches!(s, BatchStatus::Failed(_))),
+    ));
+    let warnings: usize = entries.iter().map(|e| e.warnings.len()).sum();
+    if warnings > 0 {
+        out.push_str(&format!(", {} Warnung(en)", warnings));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_base() {
+        assert_eq!(glob_base("configs/**/*.yaml"), PathBuf::from("configs"));
+        assert_eq!(glob_base("*.json"), PathBuf::new());
+        assert_eq!(glob_base("a/b/c.toml"), PathBuf::from("a/b"));
+    }
+
+    #[test]
+    fn test_mirrors_tree_and_skips_unchanged() {
+        let dir = std::env::temp_dir().join(format!("asp_batch_{}", std::process::id()));
+        let src = dir.join("configs");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("app.yaml"), "name: app\n").unwrap();
+        fs::write(src.join("nested/db.yaml"), "port: 5432\n").unwrap();
+
+        let pattern = format!("{}/**/*.yaml", src.display());
+        let out = dir.join("build");
+
+        let registry = FormatRegistry::default();
+        let toml = registry.resolve("toml").unwrap();
+        let first = convert_batch(&registry, &pattern, toml, &out, false, &ConvertOptions::default()).unwrap();
+        let second = convert_batch(&registry, &pattern, toml, &out, false, &ConvertOptions::default()).unwrap();
+        let written = fs::read_to_string(out.join("nested/db.toml")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(first.len(), 2);
+        assert!(first.iter().all(|e| matches!(e.status, BatchStatus::Converted)));
+        assert!(second.iter().all(|e| matches!(e.status, BatchStatus::Skipped)));
+        assert_eq!(written.trim(), "port = 5432");
+    }
+}
@@ -0,0 +1,108 @@
+// `edit` Subcommand - ändert TOML-Dateien, ohne Kommentare oder Layout anzufassen
+//
+// Reihenfolge: erst alle --merge Dateien, dann --set und --set-string,
+// zuletzt --unset.
+
+use crate::cli::{read_input, resolve_input_format};
+use crate::document::Node;
+use crate::error::FormatError;
+use crate::formats::toml::read_toml;
+use crate::formats::toml_editor::TomlEditor;
+use crate::options::ConvertOptions;
+use crate::registry::FormatRegistry;
+
+/// Änderungen für eine Datei
+#[derive(Debug, Default)]
+pub struct EditOps {
+    /// `KEY=VALUE`, der Wert wird als TOML gelesen
+    pub set: Vec<String>,
+    /// `KEY=VALUE`, der Wert wird unverändert als String gesetzt
+    pub set_string: Vec<String>,
+    /// Punkt-Pfade der zu entfernenden Keys
+    pub unset: Vec<String>,
+    /// Dateien in beliebigem Format, die hineingemergt werden
+    pub merge: Vec<String>,
+}
+
+/// Wendet die Änderungen an und gibt das neue TOML zurück
+pub fn edit_file(registry: &FormatRegistry, path: &str, ops: &EditOps) -> Result<String, FormatError> {
+    let content = read_input(path)?;
+    let format = resolve_input_format(registry, path, None, &content)?;
+    if format.name() != "toml" {
+        return Err(FormatError::InvalidFormat(format!(
+            "edit unterstützt nur TOML, gefunden: {}",
+            format.name()
+        )));
+    }
+    let mut editor = TomlEditor::parse(&content)?;
+
+    for overlay_path in &ops.merge {
+        let overlay = read_input(overlay_path)?;
+        let overlay_format = resolve_input_format(registry, overlay_path, None, &overlay)?;
+        editor.merge(&overlay_format.read_with(&overlay, &ConvertOptions::default())?)?;
+    }
+
+    for assignment in &ops.set {
+        let (key, raw) = split_assignment("--set", assignment)?;
+        editor.set(key, &parse_value(raw.trim())?)?;
+    }
+    for assignment in &ops.set_string {
+        let (key, raw) = split_assignment("--set-string", assignment)?;
+        editor.set(key, &Node::string(raw))?;
+    }
+
+    for key in &ops.unset {
+        if !editor.remove(key)? {
+            return Err(FormatError::InvalidFormat(format!("Key nicht gefunden: {}", key)));
+        }
+    }
+
+    Ok(editor.to_string())
+}
+
+fn split_assignment<'a>(option: &str, assignment: &'a str) -> Result<(&'a str, &'a str), FormatError> {
+    let (key, raw) = assignment.split_once('=').ok_or_else(|| {
+        FormatError::InvalidFormat(format!("{} erwartet KEY=VALUE, gefunden: {}", option, assignment))
+    })?;
+    Ok((key.trim(), raw))
+}
+
+/// TOML-Wert wie `8080`, `true`, `"text"` oder `["a", "b"]`
+///
+/// Alles, was kein einzelner TOML-Wert ist (z.B. `localhost` ohne
+/// Anführungszeichen oder `1\nother = 2`), ist ein Fehler - Strings ohne
+/// Anführungszeichen setzt `--set-string`.
+fn parse_value(raw: &str) -> Result<Node, FormatError> {
+    let invalid = |reason: &str| {
+        FormatError::InvalidFormat(format!(
+            "--set: {} ist kein TOML-Wert ({}); Strings in Anführungszeichen oder mit --set-string setzen",
+            raw, reason
+        ))
+    };
+    let doc = read_toml(&format!("value = {}", raw)).map_err(|e| match e {
+        FormatError::ParseError { message, .. } => invalid(&message),
+        other => other,
+    })?;
+    match doc.root.as_map() {
+        Some(map) if map.len() == 1 => map.get("value").cloned().ok_or_else(|| invalid("kein Wert")),
+        _ => Err(invalid("mehr als ein Wert")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::Value;
+
+    #[test]
+    fn test_parse_value() {
+        assert!(matches!(parse_value("8080").unwrap().value, Value::Integer(8080)));
+        assert!(matches!(parse_value("true").unwrap().value, Value::Bool(true)));
+        assert_eq!(parse_value("\"8080\"").unwrap().as_str(), Some("8080"));
+
+        // Kein stilles Ausweichen auf Strings, keine zusätzlichen Keys
+        assert!(parse_value("localhost").is_err());
+        assert!(parse_value("1\nother = 2").is_err());
+        assert!(parse_value("1 2").is_err());
+    }
+}
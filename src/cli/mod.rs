@@ -0,0 +1,227 @@
+// CLI-Hilfsfunktionen - nur mit "cli" Feature kompiliert
+
+use std::fs;
+use std::io::{self, Read, Write};
+
+use crate::detect::{detect_format, format_from_path};
+use crate::error::FormatError;
+use crate::registry::{Format, FormatRegistry};
+use crate::stream::PendingFile;
+use crate::warning::ConversionWarning;
+
+pub mod args;
+pub mod batch;
+pub mod edit;
+pub mod validate;
+
+/// Pfad-Platzhalter für stdin (Eingabe) bzw. stdout (Ausgabe)
+pub const STDIO: &str = "-";
+
+/// Liest eine Datei oder, bei "-", stdin komplett ein
+pub fn read_input(path: &str) -> Result<String, FormatError> {
+    if path == STDIO {
+        let mut content = String::new();
+        io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von stdin: {}", e)))?;
+        return Ok(content);
+    }
+
+    fs::read_to_string(path)
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", path, e)))
+}
+
+/// Schreibt in eine Datei oder, bei "-", nach stdout
+///
+/// Auf stdout wird ein abschliessender Zeilenumbruch ergänzt, damit die
+/// Ausgabe in Pipes und im Terminal sauber endet.
+pub fn write_output(path: &str, content: &str) -> Result<(), FormatError> {
+    if path == STDIO {
+        let mut stdout = io::stdout().lock();
+        let newline = if content.ends_with('\n') || content.is_empty() { "" } else { "\n" };
+        return write!(stdout, "{}{}", content, newline)
+            .and_then(|_| stdout.flush())
+            .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach stdout: {}", e)));
+    }
+
+    fs::write(path, content)
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", path, e)))
+}
+
+/// Ausgabe-Stream in eine Datei oder, bei "-", nach stdout
+///
+/// Gegenstück zu `write_output` für Streaming: in eine Datei wird über
+/// `PendingFile` geschrieben, erst `finish` ersetzt sie. Bricht die
+/// Konvertierung vorher ab, bleibt die alte Datei unverändert (bzw. es
+/// entsteht keine). Auf stdout ergänzt `finish` den abschliessenden
+/// Zeilenumbruch.
+pub struct OutputStream {
+    path: String,
+    inner: Option<Output>,
+    /// Zuletzt geschriebenes Byte
+    last: Option<u8>,
+}
+
+enum Output {
+    Stdout(io::StdoutLock<'static>),
+    File(PendingFile),
+}
+
+impl OutputStream {
+    pub fn new(path: &str) -> Self {
+        OutputStream { path: path.to_string(), inner: None, last: None }
+    }
+
+    fn inner(&mut self) -> io::Result<&mut Output> {
+        if self.inner.is_none() {
+            self.inner = Some(match self.path == STDIO {
+                true => Output::Stdout(io::stdout().lock()),
+                false => Output::File(PendingFile::create(&self.path)?),
+            });
+        }
+        Ok(self.inner.as_mut().expect("gerade gesetzt"))
+    }
+
+    /// Zeilenumbruch auf stdout ergänzen, Puffer leeren und die Datei
+    /// ersetzen (legt auch eine leere Ausgabedatei an)
+    pub fn finish(mut self) -> Result<(), FormatError> {
+        let newline = self.path == STDIO && self.last.is_some_and(|b| b != b'\n');
+        let result = match newline {
+            true => self.write_all(b"\n").and_then(|_| self.flush()),
+            false => self.inner().and_then(|inner| inner.flush()),
+        };
+        let result = result.and_then(|_| match self.inner.take() {
+            Some(Output::File(file)) => file.commit(),
+            _ => Ok(()),
+        });
+        result.map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", display_name(&self.path), e)))
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Stdout(stdout) => stdout.write(buf),
+            Output::File(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Stdout(stdout) => stdout.flush(),
+            Output::File(file) => file.flush(),
+        }
+    }
+}
+
+impl Write for OutputStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner()?.write(buf)?;
+        if written > 0 {
+            self.last = Some(buf[written - 1]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.inner {
+            Some(inner) => inner.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Pfad mit laufender Nummer vor der Endung: "out.toml", 2 → "out-2.toml"
+pub fn numbered_path(path: &str, number: usize) -> String {
+    let path = std::path::Path::new(path);
+    let stem = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, number, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, number),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// Anzeigename für Meldungen ("<stdin>" statt "-")
+pub fn display_name(path: &str) -> &str {
+    if path == STDIO { "<stdin>" } else { path }
+}
+
+/// Bestimmt das Eingabeformat: `--from`, dann Extension, dann bekannter
+/// Dateiname, zuletzt der Inhalt (auch für stdin)
+pub fn resolve_input_format<'a>(
+    registry: &'a FormatRegistry,
+    input_path: &str,
+    from: Option<&str>,
+    content: &str,
+) -> Result<&'a dyn Format, FormatError> {
+    if let Some(name) = from {
+        return registry.resolve(name);
+    }
+    if input_path != STDIO
+        && let Some(format) = registry.for_path(input_path)
+    {
+        return Ok(format);
+    }
+
+    let hint = (input_path != STDIO).then_some(input_path);
+    let detected = hint
+        .and_then(format_from_path)
+        .or_else(|| detect_format(content, hint))
+        .ok_or_else(|| {
+            FormatError::InvalidFormat(format!(
+                "Format von {} konnte nicht erkannt werden - bitte --from angeben",
+                display_name(input_path)
+            ))
+        })?;
+    registry.resolve(detected.as_str())
+}
+
+/// Rendert einen Fehler für die Konsole
+///
+/// Parse-Fehler mit Position werden im rustc-Stil mit Dateiname, Zeile,
+/// Spalte und markiertem Ausschnitt ausgegeben:
+///
+/// ```text
+/// ✗ Fehler[json]: expected `:`
+///  --> data.json:3:7
+///   |
+/// 3 |   "b" 2
+///   |       ^
+/// ```
+pub fn render_error(error: &FormatError, source_name: &str) -> String {
+    match error {
+        FormatError::ParseError { format, message, span: Some(span), snippet } => {
+            let gutter = " ".repeat(span.line.to_string().len());
+            let mut out = format!(
+                "✗ Fehler[{}]: {}\n{}--> {}:{}:{}",
+                format, message, gutter, source_name, span.line, span.column
+            );
+            if let Some(snippet) = snippet {
+                out.push('\n');
+                out.push_str(snippet);
+            }
+            out
+        }
+        FormatError::ParseError { format, message, span: None, .. } => {
+            format!("✗ Fehler[{}]: {}\n --> {}", format, message, source_name)
+        }
+        other => format!("✗ Fehler: {}", other),
+    }
+}
+
+/// Rendert eine Warnung für die Konsole, z.B.
+///
+/// ```text
+/// ⚠ Warnung[null-omitted]: null-Wert ausgelassen (TOML kennt kein null)
+///  --> config.json#/proxy
+/// ```
+pub fn render_warning(warning: &ConversionWarning, source_name: &str) -> String {
+    format!(
+        "⚠ Warnung[{}]: {}\n --> {}#{}",
+        warning.kind.as_str(),
+        warning.message,
+        source_name,
+        warning.path
+    )
+}
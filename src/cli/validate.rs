@@ -0,0 +1,124 @@
+// `validate` Subcommand - prüft die Syntax beliebig vieler Dateien
+//
+// Fehler brechen nicht ab: jede Datei wird geprüft, damit ein Pre-Commit-Hook
+// alle Probleme auf einmal meldet.
+
+use std::collections::HashSet;
+
+use crate::cli::{display_name, read_input, resolve_input_format, STDIO};
+use crate::error::FormatError;
+use crate::options::ConvertOptions;
+use crate::registry::FormatRegistry;
+
+/// Prüfergebnis für eine Datei (bzw. ein Muster ohne Treffer)
+pub struct FileReport {
+    /// Pfad wie angezeigt, "<stdin>" für "-"
+    pub path: String,
+    /// Name des erkannten Formats oder der Fehler
+    pub result: Result<String, FormatError>,
+}
+
+impl FileReport {
+    pub fn is_valid(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// Prüft alle Dateien, die auf die Muster passen
+///
+/// Muster mit `*`, `?` oder `[` werden als Glob expandiert (`**` für
+/// Unterordner), alles andere gilt als Pfad. Doppelte Treffer werden nur
+/// einmal geprüft.
+pub fn validate_files(
+    registry: &FormatRegistry,
+    patterns: &[String],
+    from: Option<&str>,
+    options: &ConvertOptions,
+) -> Vec<FileReport> {
+    let mut reports = Vec::new();
+    let mut seen = HashSet::new();
+
+    for pattern in patterns {
+        let paths = match expand_pattern(pattern) {
+            Ok(paths) => paths,
+            Err(e) => {
+                reports.push(FileReport { path: pattern.clone(), result: Err(e) });
+                continue;
+            }
+        };
+
+        for path in paths {
+            if seen.insert(path.clone()) {
+                let result = validate_file(registry, &path, from, options);
+                reports.push(FileReport { path: display_name(&path).to_string(), result });
+            }
+        }
+    }
+
+    reports
+}
+
+/// Prüft eine einzelne Datei und gibt den Namen des Formats zurück
+pub fn validate_file(
+    registry: &FormatRegistry,
+    path: &str,
+    from: Option<&str>,
+    options: &ConvertOptions,
+) -> Result<String, FormatError> {
+    let content = read_input(path)?;
+    let format = resolve_input_format(registry, path, from, &content)?;
+    format.validate(&content, options)?;
+    Ok(format.name().to_string())
+}
+
+/// Expandiert ein Glob-Muster; ohne Treffer ist das ein Fehler
+fn expand_pattern(pattern: &str) -> Result<Vec<String>, FormatError> {
+    if pattern == STDIO || !pattern.contains(['*', '?', '[']) {
+        return Ok(vec![pattern.to_string()]);
+    }
+
+    let entries = glob::glob(pattern)
+        .map_err(|e| FormatError::InvalidFormat(format!("Ungültiges Muster {}: {}", pattern, e)))?;
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| FormatError::IoError(e.to_string()))?;
+        if path.is_file() {
+            paths.push(path.to_string_lossy().into_owned());
+        }
+    }
+
+    if paths.is_empty() {
+        return Err(FormatError::IoError(format!("Keine Dateien gefunden für {}", pattern)));
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_reports_every_file() {
+        let dir = std::env::temp_dir().join(format!("asp_validate_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("ok.json"), "{\"a\": 1}").unwrap();
+        fs::write(dir.join("broken.yaml"), "a: 1\na: 2\n").unwrap();
+
+        let registry = FormatRegistry::default();
+        let patterns = vec![
+            format!("{}/*", dir.display()),
+            format!("{}/*.toml", dir.display()),
+        ];
+        let reports = validate_files(&registry, &patterns, None, &ConvertOptions::default());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(reports.len(), 3);
+        assert!(reports[0].path.ends_with("broken.yaml"));
+        assert_eq!(reports[0].result.as_ref().unwrap_err().span().map(|s| s.line), Some(2));
+        assert_eq!(reports[1].result.as_deref().ok(), Some("json"));
+        // Muster ohne Treffer wird als Fehler gemeldet
+        assert!(!reports[2].is_valid());
+    }
+}
@@ -1 +1,287 @@
-// Format Autodetektion hier implementieren
\ No newline at end of file
+// Format-Autodetektion anhand von Inhalt und (optional) Dateiname
+
+use serde::Deserialize;
+
+use crate::format::FileFormat;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Ergebnis einer Format-Erkennung
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Detection {
+    /// Erkanntes Format
+    pub format: FileFormat,
+    /// Konfidenz zwischen 0.0 (geraten) und 1.0 (sicher)
+    pub confidence: f32,
+}
+
+/// Dateinamen ohne (bekannte) Extension, deren Format trotzdem feststeht
+const KNOWN_FILENAMES: &[(&str, FileFormat)] = &[
+    ("cargo.lock", FileFormat::Toml),
+    ("pipfile", FileFormat::Toml),
+    ("poetry.lock", FileFormat::Toml),
+    (".babelrc", FileFormat::Json),
+    (".eslintrc", FileFormat::Json),
+    (".prettierrc", FileFormat::Json),
+    (".clang-format", FileFormat::Yaml),
+    (".yamllint", FileFormat::Yaml),
+];
+
+/// Bonus, den ein passender Dateiname auf die Inhalts-Konfidenz gibt
+const FILENAME_BONUS: f32 = 0.3;
+
+/// Darunter ist `detect_format` nur geraten (z.B. Fliesstext als YAML-String)
+/// und gibt `None` zurück
+const MIN_CONFIDENCE: f32 = 0.2;
+
+/// Erkennt das Format anhand der Dateiendung oder eines bekannten Dateinamens
+///
+/// # Beispiel
+/// ```
+/// use asp_cli::{detect::format_from_path, FileFormat};
+///
+/// assert_eq!(format_from_path("config.yml"), Some(FileFormat::Yaml));
+/// assert_eq!(format_from_path("Cargo.lock"), Some(FileFormat::Toml));
+/// assert_eq!(format_from_path("data.txt"), None);
+/// ```
+pub fn format_from_path(path: impl AsRef<Path>) -> Option<FileFormat> {
+    let path = path.as_ref();
+
+    if let Some(format) = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| FileFormat::from_str(ext).ok())
+    {
+        return Some(format);
+    }
+
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    KNOWN_FILENAMES
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, format)| *format)
+}
+
+/// Bewertet den Inhalt für alle Formate und gibt die Kandidaten absteigend
+/// nach Konfidenz sortiert zurück (Formate ohne Treffer fehlen in der Liste)
+///
+/// Der optionale Dateiname dient nur als Hinweis: eine passende Endung erhöht
+/// die Konfidenz, ersetzt aber nicht die Inhaltsprüfung.
+///
+/// # Beispiel
+/// ```
+/// use asp_cli::{detect::detect, FileFormat};
+///
+/// let ranked = detect("name = \"Test\"\nvalue = 42\n", None);
+/// assert_eq!(ranked[0].format, FileFormat::Toml);
+/// ```
+pub fn detect(input: &str, filename: Option<&str>) -> Vec<Detection> {
+    // Leerer Inhalt ist in jedem Format "gültig" und sagt nichts aus
+    if input.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let hint = filename.and_then(format_from_path);
+
+    let mut ranked: Vec<Detection> = [
+        (FileFormat::Json, score_json(input)),
+        (FileFormat::Ndjson, score_ndjson(input)),
+        (FileFormat::Toml, score_toml(input)),
+        (FileFormat::Yaml, score_yaml(input)),
+        (FileFormat::Csv, score_csv(input)),
+        (FileFormat::Xml, score_xml(input)),
+    ]
+    .into_iter()
+    .filter(|(_, score)| *score > 0.0)
+    .map(|(format, score)| {
+        let bonus = if hint == Some(format) { FILENAME_BONUS } else { 0.0 };
+        Detection { format, confidence: (score + bonus).min(1.0) }
+    })
+    .collect();
+
+    // Stabile Sortierung: bei Gleichstand gewinnt die Reihenfolge oben
+    ranked.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    ranked
+}
+
+/// Gibt das wahrscheinlichste Format zurück (oder None wenn nichts sicher
+/// genug passt, siehe `MIN_CONFIDENCE`)
+pub fn detect_format(input: &str, filename: Option<&str>) -> Option<FileFormat> {
+    detect(input, filename)
+        .first()
+        .filter(|d| d.confidence >= MIN_CONFIDENCE)
+        .map(|d| d.format)
+}
+
+// ============================================================================
+// Heuristiken pro Format
+// ============================================================================
+
+/// JSON: muss mit { oder [ beginnen; vollständig parsebar ist fast sicher
+fn score_json(input: &str) -> f32 {
+    let trimmed = input.trim_start();
+    if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
+        return 0.0;
+    }
+
+    match serde_json::from_str::<serde_json::Value>(input) {
+        Ok(_) => 0.95,
+        // Sieht nach JSON aus, ist aber kaputt (oder YAML-Flow / TOML-Section)
+        Err(_) => 0.3,
+    }
+}
+
+/// NDJSON: mehrere Zeilen, jede ein eigenes JSON-Objekt bzw. -Array
+/// (geprüft werden die ersten 50 Zeilen)
+fn score_ndjson(input: &str) -> f32 {
+    let lines: Vec<&str> = input.lines().map(str::trim).filter(|l| !l.is_empty()).take(50).collect();
+    if lines.len() < 2 {
+        return 0.0;
+    }
+
+    let all_values = lines.iter().all(|line| {
+        (line.starts_with('{') || line.starts_with('['))
+            && serde_json::from_str::<serde_json::Value>(line).is_ok()
+    });
+    if all_values { 0.9 } else { 0.0 }
+}
+
+/// TOML: parsebar und mit mindestens einer Zuweisung oder Tabelle
+fn score_toml(input: &str) -> f32 {
+    let has_toml_syntax = input.lines().map(str::trim).any(|line| {
+        (line.starts_with('[') && line.ends_with(']')) || is_toml_assignment(line)
+    });
+    if !has_toml_syntax {
+        return 0.0;
+    }
+
+    match toml::from_str::<toml::Value>(input) {
+        Ok(_) => 0.9,
+        Err(_) => 0.1,
+    }
+}
+
+/// Prüft ob eine Zeile wie `key = value` aussieht
+fn is_toml_assignment(line: &str) -> bool {
+    match line.split_once('=') {
+        Some((key, _)) => {
+            let key = key.trim();
+            !key.is_empty()
+                && key
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '"' | '\'' | ' '))
+        }
+        None => false,
+    }
+}
+
+/// YAML: fast jeder Text ist gültiges YAML, deshalb zählt nur eine Struktur
+/// (Mapping oder Sequenz) als Treffer. Bei mehreren Dokumenten (`---`)
+/// müssen alle gültig sein und mindestens eines strukturiert.
+fn score_yaml(input: &str) -> f32 {
+    let mut structured = false;
+    for document in serde_yaml::Deserializer::from_str(input) {
+        match serde_yaml::Value::deserialize(document) {
+            Ok(serde_yaml::Value::Mapping(_)) | Ok(serde_yaml::Value::Sequence(_)) => structured = true,
+            Ok(_) => {}
+            Err(_) => return 0.0,
+        }
+    }
+    if !structured {
+        return 0.05;
+    }
+
+    // Block-Syntax ist typisch YAML, Flow-Syntax eher JSON
+    let trimmed = input.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        0.5
+    } else {
+        0.8
+    }
+}
+
+/// CSV: mindestens zwei Spalten und gleich viele Felder in jeder Zeile
+/// (Trennzeichen wird wie beim Lesen erkannt)
+fn score_csv(input: &str) -> f32 {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(crate::formats::csv::sniff_delimiter(input))
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(input.as_bytes());
+
+    let mut columns = None;
+    let mut rows = 0;
+    for record in reader.records().take(50) {
+        let Ok(record) = record else {
+            return 0.0;
+        };
+        match columns {
+            None => columns = Some(record.len()),
+            Some(n) if n != record.len() => return 0.0,
+            Some(_) => {}
+        }
+        rows += 1;
+    }
+
+    match columns {
+        Some(n) if n >= 2 && rows >= 2 => 0.7,
+        Some(n) if n >= 2 => 0.4,
+        _ => 0.0,
+    }
+}
+
+/// XML: beginnt mit "<"; Deklaration oder erfolgreiches Parsen ist eindeutig
+fn score_xml(input: &str) -> f32 {
+    let trimmed = input.trim_start();
+    if !trimmed.starts_with('<') {
+        return 0.0;
+    }
+    if trimmed.starts_with("<?xml") {
+        return 0.95;
+    }
+
+    match crate::formats::xml::read_xml(input) {
+        Ok(_) => 0.9,
+        Err(_) => 0.3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(format_from_path("data.json"), Some(FileFormat::Json));
+        assert_eq!(format_from_path("dir/Cargo.lock"), Some(FileFormat::Toml));
+        assert_eq!(format_from_path("config"), None);
+        assert_eq!(format_from_path("Dockerfile.env"), None);
+    }
+
+    #[test]
+    fn test_detect_content() {
+        assert_eq!(detect_format(r#"{"name": "Test"}"#, None), Some(FileFormat::Json));
+        assert_eq!(detect_format("[server]\nport = 8080\n", None), Some(FileFormat::Toml));
+        assert_eq!(detect_format("server:\n  port: 8080\n", None), Some(FileFormat::Yaml));
+        assert_eq!(detect_format("kind: Service\n---\nkind: Pod\n", None), Some(FileFormat::Yaml));
+        assert_eq!(detect_format("name,age\nAlice,30\nBob,25\n", None), Some(FileFormat::Csv));
+        assert_eq!(detect_format("{\"a\": 1}\n{\"a\": 2}\n", None), Some(FileFormat::Ndjson));
+        assert_eq!(detect_format("", None), None);
+
+        // Fliesstext ist zwar gültiges YAML, aber kein erkanntes Format
+        assert_eq!(detect_format("Das ist nur Text.\nOhne Struktur.\n", None), None);
+        assert_eq!(detect_format("Hallo Welt\n", Some("notes.yaml")), Some(FileFormat::Yaml));
+    }
+
+    #[test]
+    fn test_detect_ranking_and_hint() {
+        // JSON ist auch gültiges YAML, JSON muss aber vorne liegen
+        let ranked = detect(r#"[1, 2, 3]"#, None);
+        assert_eq!(ranked[0].format, FileFormat::Json);
+        assert!(ranked.iter().any(|d| d.format == FileFormat::Yaml));
+
+        let without_hint = detect("a: 1\n", None)[0].confidence;
+        let with_hint = detect("a: 1\n", Some("settings.yml"))[0].confidence;
+        assert!(with_hint > without_hint);
+    }
+}
@@ -0,0 +1,473 @@
+// Format-neutrales Zwischenmodell (IR) für alle Konvertierungen
+//
+// Jedes Format liest in ein `Document` und schreibt aus einem `Document`.
+// Dadurch gehen Informationen wie Key-Reihenfolge, TOML-Datetimes, YAML-Tags
+// oder grosse Ganzzahlen nicht mehr auf dem Umweg über serde_json::Value verloren.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Datum/Uhrzeit nach RFC 3339 (inkl. lokaler Varianten ohne Offset)
+///
+/// TOMLs Datetime-Modell deckt alle Varianten ab (Offset-, lokale Datetime,
+/// nur Datum, nur Zeit) und wird deshalb als neutraler Typ verwendet.
+pub type DateTime = toml::value::Datetime;
+
+/// Position eines Knotens im Quelltext
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    /// Byte-Offset ab Anfang der Eingabe
+    pub offset: usize,
+    /// Länge in Bytes (0 wenn unbekannt)
+    pub len: usize,
+    /// Zeile, 1-basiert
+    pub line: usize,
+    /// Spalte in Zeichen, 1-basiert
+    pub column: usize,
+}
+
+/// Ein vollständig eingelesenes Dokument
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub root: Node,
+}
+
+impl Document {
+    pub fn new(root: Node) -> Self {
+        Document { root }
+    }
+
+    /// Sortiert die Keys aller Maps im Dokument rekursiv
+    pub fn sort_keys(&mut self) {
+        self.root.sort_keys();
+    }
+}
+
+/// Ein Knoten im Dokument: Wert plus Metadaten
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub value: Value,
+    /// Format-spezifischer Tag (z.B. YAML `!Ref`), falls vorhanden
+    pub tag: Option<String>,
+    /// Herkunft im Quelltext, sofern der Reader sie kennt
+    pub span: Option<Span>,
+    /// Kommentare und Leerzeilen (an Map-Keys und Array-Einträgen, z.B. aus YAML)
+    pub comment: Option<Box<Comment>>,
+    /// YAML-Anker bzw. -Alias; der Wert eines Alias ist bereits aufgelöst
+    pub anchor: Option<Box<Anchor>>,
+}
+
+/// Herkunft eines Knotens aus YAML-Ankern (`&name`) und -Aliasen (`*name`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Anchor {
+    /// Knoten definiert den Anker
+    Define(String),
+    /// Knoten ist eine Kopie des verankerten Knotens
+    Alias(String),
+}
+
+/// Kommentare zu einem Eintrag, jeweils ohne das führende `#`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Comment {
+    /// Kommentarzeilen direkt über dem Eintrag
+    pub before: Vec<String>,
+    /// Kommentar am Ende der Zeile
+    pub trailing: Option<String>,
+    /// Leerzeilen über den Kommentarzeilen bzw. über dem Eintrag
+    pub blank_lines: usize,
+    /// Leerzeilen zwischen den Kommentarzeilen und dem Eintrag
+    pub blank_lines_after: usize,
+}
+
+/// Vergleicht nur Wert und Tag - Position, Kommentare und Anker sind Metadaten
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.tag == other.tag
+    }
+}
+
+/// Typisierte Werte
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    /// Ganzzahl ausserhalb von i64, als Dezimalziffern
+    BigInt(String),
+    Float(f64),
+    /// Dezimalzahl in Originalschreibweise (ohne Rundung über f64)
+    Decimal(String),
+    String(String),
+    Bytes(Vec<u8>),
+    DateTime(DateTime),
+    Array(Vec<Node>),
+    Map(Map),
+}
+
+impl Value {
+    /// Name des Typs für Fehlermeldungen
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Integer(_) | Value::BigInt(_) => "integer",
+            Value::Float(_) | Value::Decimal(_) => "float",
+            Value::String(_) => "string",
+            Value::Bytes(_) => "bytes",
+            Value::DateTime(_) => "datetime",
+            Value::Array(_) => "array",
+            Value::Map(_) => "map",
+        }
+    }
+}
+
+impl Node {
+    pub fn new(value: Value) -> Self {
+        Node { value, tag: None, span: None, comment: None, anchor: None }
+    }
+
+    pub fn null() -> Self {
+        Node::new(Value::Null)
+    }
+
+    pub fn string(s: impl Into<String>) -> Self {
+        Node::new(Value::String(s.into()))
+    }
+
+    pub fn array(items: Vec<Node>) -> Self {
+        Node::new(Value::Array(items))
+    }
+
+    pub fn map(map: Map) -> Self {
+        Node::new(Value::Map(map))
+    }
+
+    pub fn with_span(mut self, span: Option<Span>) -> Self {
+        self.span = span;
+        self
+    }
+
+    pub fn with_tag(mut self, tag: Option<String>) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    pub fn with_comment(mut self, comment: Option<Comment>) -> Self {
+        self.comment = comment.map(Box::new);
+        self
+    }
+
+    pub fn with_anchor(mut self, anchor: Option<Anchor>) -> Self {
+        self.anchor = anchor.map(Box::new);
+        self
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Node>> {
+        match &self.value {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&Map> {
+        match &self.value {
+            Value::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self.value, Value::Null)
+    }
+
+    /// Darstellung als Map-Key für Formate, die nur String-Keys kennen
+    pub fn to_key_string(&self) -> String {
+        match &self.value {
+            Value::String(s) => s.clone(),
+            Value::Null => "null".to_string(),
+            other => scalar_to_string(other).unwrap_or_else(|| format!("<{}>", other.type_name())),
+        }
+    }
+
+    /// Sortiert die Keys dieses Knotens und aller Kinder
+    pub fn sort_keys(&mut self) {
+        match &mut self.value {
+            Value::Array(items) => items.iter_mut().for_each(Node::sort_keys),
+            Value::Map(map) => {
+                map.sort_keys();
+                map.entries.iter_mut().for_each(|(_, value)| value.sort_keys());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Textdarstellung eines Skalars (None für Arrays und Maps)
+pub fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => Some(String::new()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Integer(i) => Some(i.to_string()),
+        Value::BigInt(s) | Value::Decimal(s) | Value::String(s) => Some(s.clone()),
+        Value::Float(f) => Some(format_float(*f)),
+        Value::Bytes(bytes) => Some(crate::formats::utils::base64_encode(bytes)),
+        Value::DateTime(dt) => Some(dt.to_string()),
+        Value::Array(_) | Value::Map(_) => None,
+    }
+}
+
+/// Zahl aus Text ohne Genauigkeitsverlust
+///
+/// Ganzzahlen ausserhalb von i64 werden zu `BigInt`, Dezimalzahlen, die f64
+/// nicht exakt wiedergibt (zu viele Stellen oder ausserhalb von f64), zu
+/// `Decimal`. Massgeblich ist die kürzeste Darstellung des f64: stimmen ihre
+/// Ziffern mit der Eingabe überein, bleibt es ein `Float`.
+/// `None`, wenn der Text keine Zahl ist (auch "NaN", "inf").
+///
+/// ```
+/// use asp_cli::document::{parse_number, Value};
+///
+/// assert_eq!(parse_number("42"), Some(Value::Integer(42)));
+/// assert_eq!(parse_number("18446744073709551616"), Some(Value::BigInt("18446744073709551616".into())));
+/// assert_eq!(parse_number("19.99"), Some(Value::Float(19.99)));
+/// assert_eq!(parse_number("3.141592653589793"), Some(Value::Float(std::f64::consts::PI)));
+/// assert_eq!(parse_number("0.12345678901234567890"), Some(Value::Decimal("0.12345678901234567890".into())));
+/// assert_eq!(parse_number("abc"), None);
+/// ```
+pub fn parse_number(text: &str) -> Option<Value> {
+    let unsigned = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let canonical = text.strip_prefix('+').unwrap_or(text);
+    if !unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+
+    if unsigned.bytes().all(|b| b.is_ascii_digit()) {
+        return Some(match canonical.parse::<i64>() {
+            Ok(i) => Value::Integer(i),
+            Err(_) => Value::BigInt(canonical.to_string()),
+        });
+    }
+
+    if !unsigned.bytes().all(|b| b.is_ascii_digit() || matches!(b, b'.' | b'e' | b'E' | b'+' | b'-')) {
+        return None;
+    }
+    let f = canonical.parse::<f64>().ok()?;
+    let exact = f.is_finite()
+        && normalized_digits(unsigned).is_some_and(|digits| normalized_digits(&format!("{:e}", f.abs())) == Some(digits));
+    if exact {
+        Some(Value::Float(f))
+    } else {
+        Some(Value::Decimal(canonical.to_string()))
+    }
+}
+
+/// Ziffern ohne führende/folgende Nullen und Exponent der ersten Ziffer
+///
+/// "0.0120" und "1.2e-2" → ("12", -1), damit lassen sich Schreibweisen
+/// derselben Zahl vergleichen. `None` bei unlesbarem Exponenten.
+fn normalized_digits(number: &str) -> Option<(String, i64)> {
+    let (mantissa, exponent) = match number.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+        None => (number, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", int, frac);
+    let leading = digits.len() - digits.trim_start_matches('0').len();
+    let digits = digits.trim_start_matches('0').trim_end_matches('0');
+    if digits.is_empty() {
+        return Some((String::new(), 0));
+    }
+    Some((digits.to_string(), int.len() as i64 - leading as i64 + exponent))
+}
+
+/// Formatiert Floats so wie serde_json (ganze Zahlen behalten ".0")
+fn format_float(f: f64) -> String {
+    if f.is_finite() && f.fract() == 0.0 && f.abs() < 1e16 {
+        format!("{:.1}", f)
+    } else {
+        f.to_string()
+    }
+}
+
+/// Geordnete Map - Keys bleiben in Einfügereihenfolge und dürfen
+/// beliebige Knoten sein (YAML erlaubt z.B. Zahlen oder Booleans als Key)
+#[derive(Debug, Clone, Default)]
+pub struct Map {
+    entries: Vec<(Node, Node)>,
+    /// Positionen der Einträge nach Key-Text (`to_key_string`): gleiche Keys
+    /// haben denselben Text, verglichen wird nur innerhalb davon
+    index: HashMap<String, Vec<usize>>,
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Map::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Hängt einen Eintrag an, ohne auf Duplikate zu prüfen
+    ///
+    /// Nur verwenden, wenn der Parser eindeutige Keys bereits garantiert.
+    pub fn push(&mut self, key: Node, value: Node) {
+        self.index.entry(key.to_key_string()).or_default().push(self.entries.len());
+        self.entries.push((key, value));
+    }
+
+    /// Fügt ein oder ersetzt einen bestehenden Eintrag (Position bleibt erhalten)
+    pub fn insert(&mut self, key: Node, value: Node) -> Option<Node> {
+        let slots = self.index.entry(key.to_key_string()).or_default();
+        match slots.iter().find(|&&i| self.entries[i].0 == key) {
+            Some(&i) => Some(std::mem::replace(&mut self.entries[i].1, value)),
+            None => {
+                slots.push(self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn contains_key(&self, key: &Node) -> bool {
+        self.index
+            .get(&key.to_key_string())
+            .is_some_and(|slots| slots.iter().any(|&i| self.entries[i].0 == *key))
+    }
+
+    /// Sucht einen Eintrag über einen String-Key
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        let i = self.position(key)?;
+        Some(&self.entries[i].1)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Node> {
+        let i = self.position(key)?;
+        Some(&mut self.entries[i].1)
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        self.index.get(key)?.iter().copied().find(|&i| self.entries[i].0.as_str() == Some(key))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Node, &Node)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    /// Keys nur an Metadaten ändern (z.B. Kommentare), nicht am Wert - der
+    /// Index geht vom bisherigen Key-Text aus
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&mut Node, &mut Node)> {
+        self.entries.iter_mut().map(|(k, v)| (k, v))
+    }
+
+    /// Sortiert die Einträge nach Key (stabil, Werte werden nicht angefasst)
+    pub fn sort_keys(&mut self) {
+        self.entries.sort_by_cached_key(|(k, _)| k.to_key_string());
+        self.index.clear();
+        for (i, (key, _)) in self.entries.iter().enumerate() {
+            self.index.entry(key.to_key_string()).or_default().push(i);
+        }
+    }
+}
+
+/// Vergleicht nur die Einträge samt Reihenfolge
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (Node, Node);
+    type IntoIter = std::vec::IntoIter<(Node, Node)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+/// Doppelte Keys wie bei `insert`: der spätere Wert gewinnt, die Position
+/// des ersten bleibt
+impl FromIterator<(Node, Node)> for Map {
+    fn from_iter<I: IntoIterator<Item = (Node, Node)>>(iter: I) -> Self {
+        let mut map = Map::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_keeps_insertion_order() {
+        let mut map = Map::new();
+        map.insert(Node::string("zeta"), Node::new(Value::Integer(1)));
+        map.insert(Node::string("alpha"), Node::new(Value::Integer(2)));
+        map.insert(Node::string("zeta"), Node::new(Value::Integer(3)));
+
+        let keys: Vec<_> = map.iter().map(|(k, _)| k.to_key_string()).collect();
+        assert_eq!(keys, vec!["zeta", "alpha"]);
+        assert_eq!(map.get("zeta").map(|n| &n.value), Some(&Value::Integer(3)));
+    }
+
+    #[test]
+    fn test_collect_replaces_duplicates() {
+        let map: Map = [("zeta", 1), ("alpha", 2), ("zeta", 3)]
+            .into_iter()
+            .map(|(k, v)| (Node::string(k), Node::new(Value::Integer(v))))
+            .chain([(Node::new(Value::Integer(1)), Node::string("eins"))])
+            .collect();
+
+        let keys: Vec<_> = map.iter().map(|(k, _)| k.to_key_string()).collect();
+        assert_eq!(keys, vec!["zeta", "alpha", "1"]);
+        assert_eq!(map.get("zeta").map(|n| &n.value), Some(&Value::Integer(3)));
+    }
+
+    #[test]
+    fn test_lookup_after_sort_keys() {
+        let mut map: Map = ["b", "a", "c"].into_iter().map(|k| (Node::string(k), Node::string(k))).collect();
+        map.sort_keys();
+        map.insert(Node::string("a"), Node::string("neu"));
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get("a").and_then(Node::as_str), Some("neu"));
+        assert!(map.contains_key(&Node::string("c")));
+    }
+
+    #[test]
+    fn test_node_equality_ignores_span() {
+        let span = Span { offset: 4, len: 1, line: 2, column: 1 };
+        assert_eq!(Node::string("a").with_span(Some(span)), Node::string("a"));
+        assert_ne!(Node::string("a").with_tag(Some("!Ref".into())), Node::string("a"));
+    }
+
+    #[test]
+    fn test_non_string_keys() {
+        let mut map = Map::new();
+        map.insert(Node::new(Value::Integer(1)), Node::string("eins"));
+        map.insert(Node::string("1"), Node::string("string-eins"));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("1").and_then(Node::as_str), Some("string-eins"));
+    }
+}
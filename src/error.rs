@@ -1,21 +1,113 @@
 // Gemeinsamer Fehler-Typen und Fehlerbehandlung hier implementieren
 
+use crate::document::Span;
+
 // dynmaisch generischer Fehler-Typ
 #[derive(Debug)] // ermöglicht das Debugging des Fehlers
 pub enum FormatError { // pub macht es für anderen modulen benutzbar
      IoError(String),
-     ParseError(String),
+     /// Syntaxfehler in der Eingabe - Position und Ausschnitt als Felder,
+     /// damit CLI und Web-Version sie selbst darstellen können
+     ParseError {
+         /// Format der fehlerhaften Eingabe, z.B. "json"
+         format: String,
+         /// Meldung des Parsers (ohne Positionsangabe)
+         message: String,
+         /// Position des Fehlers, falls der Parser sie liefert
+         span: Option<Span>,
+         /// Gerenderter Quelltext-Ausschnitt mit Markierung (^) unter der Fehlerstelle
+         snippet: Option<String>,
+     },
      SerializationError(String), // Umbenannt von FormatError, um Namenskonflikt zu vermeiden
      InvalidFormat(String), // Für ungültige/nicht unterstützte Formate
      UnknownError(String),
 }
 
+impl FormatError {
+    /// Erstellt einen ParseError und rendert den Ausschnitt aus dem Quelltext
+    pub fn parse(format: &str, message: impl Into<String>, input: &str, span: Option<Span>) -> Self {
+        FormatError::ParseError {
+            format: format.to_string(),
+            message: message.into(),
+            span,
+            snippet: span.map(|span| render_snippet(input, span)),
+        }
+    }
+
+    /// Wie `parse`, wenn nur die betroffene Zeile vorliegt (z.B. beim
+    /// zeilenweisen Lesen eines Streams) - `span.line` ist die Zeile in der
+    /// ganzen Eingabe
+    pub fn parse_line(format: &str, message: impl Into<String>, line_text: &str, span: Span) -> Self {
+        let span_chars = if span.len > 0 { 1 } else { 0 };
+        FormatError::ParseError {
+            format: format.to_string(),
+            message: message.into(),
+            span: Some(span),
+            snippet: Some(render_line(line_text, span, span_chars)),
+        }
+    }
+
+    /// Position des Fehlers im Quelltext (nur bei ParseError)
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            FormatError::ParseError { span, .. } => *span,
+            _ => None,
+        }
+    }
+}
+
+/// Rendert die betroffene Zeile im rustc-Stil:
+///
+/// ```text
+///   |
+/// 3 |   "b" 2
+///   |       ^
+/// ```
+fn render_snippet(input: &str, span: Span) -> String {
+    let line_text = input
+        .lines()
+        .nth(span.line.saturating_sub(1))
+        .unwrap_or_default();
+    let span_chars = input
+        .get(span.offset..span.offset + span.len)
+        .map(|s| s.chars().count())
+        .unwrap_or(0);
+    render_line(line_text, span, span_chars)
+}
+
+/// Rendert eine einzelne Zeile mit Marker (Länge in Zeichen)
+fn render_line(line_text: &str, span: Span, span_chars: usize) -> String {
+    let line_text = line_text.trim_end_matches(['\r', '\n']);
+    let gutter = " ".repeat(span.line.to_string().len());
+
+    // Einrückung übernimmt Tabs aus der Zeile, damit der Marker richtig steht
+    let indent: String = line_text
+        .chars()
+        .take(span.column.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+
+    let remaining = line_text.chars().count().saturating_sub(span.column.saturating_sub(1));
+    let marker = "^".repeat(span_chars.min(remaining).max(1));
+
+    format!(
+        "{gutter} |\n{line} | {line_text}\n{gutter} | {indent}{marker}",
+        line = span.line
+    )
+}
+
 // implementiert Display für FormatError, damit der Fehler als String ausgegeben werden kann
 impl std::fmt::Display for FormatError {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { // rückgabe ist ein std::fmt::Result, also Ok(()) oder Err(e)
       match self { // pattern matching, um den Fehler zu handeln
           FormatError::IoError(msg) => write!(f, "IO-Fehler: {}", msg), // write! ist eine makro, die eine string in die formatierte ausgabe schreibt
-          FormatError::ParseError(msg) => write!(f, "Parse-Fehler: {}", msg),
+          FormatError::ParseError { format, message, span, .. } => {
+              write!(f, "Parse-Fehler: Ungültiges {}: {}", format.to_uppercase(), message)?;
+              if let Some(span) = span {
+                  write!(f, " (Zeile {}, Spalte {})", span.line, span.column)?;
+              }
+              Ok(())
+          }
           FormatError::SerializationError(msg) => write!(f, "Format-Fehler: {}", msg),
           FormatError::InvalidFormat(msg) => write!(f, "Ungültiges Format: {}", msg),
           FormatError::UnknownError(msg) => write!(f, "Unbekannter Fehler: {}", msg), // Fallback für unbekannte Fehler
@@ -23,4 +115,22 @@ impl std::fmt::Display for FormatError {
   } // end of match
 } // end of impl
 
-impl std::error::Error for FormatError {} // implementiert Error für FormatError, damit der Fehler als Error ausgegeben werden kann
\ No newline at end of file
+impl std::error::Error for FormatError {} // implementiert Error für FormatError, damit der Fehler als Error ausgegeben werden kann
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_points_at_span() {
+        let input = "{\n  \"a\": 1,\n  \"b\" 2\n}";
+        let span = Span { offset: 19, len: 1, line: 3, column: 7 };
+        let error = FormatError::parse("json", "expected `:`", input, Some(span));
+
+        let FormatError::ParseError { snippet: Some(snippet), .. } = &error else {
+            panic!("snippet erwartet");
+        };
+        assert_eq!(snippet, "  |\n3 |   \"b\" 2\n  |       ^");
+        assert_eq!(error.to_string(), "Parse-Fehler: Ungültiges JSON: expected `:` (Zeile 3, Spalte 7)");
+    }
+}
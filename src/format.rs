@@ -1,72 +1,299 @@
 // Elegante Format-Konvertierung mit Enum + Trait Pattern
 
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
 use std::str::FromStr;
 use crate::error::FormatError;
 
-// Import aller String-Konvertierungsfunktionen
-use crate::formats::json::{json_to_json_string, json_to_toml_string, json_to_yaml_string, json_to_csv_string};
-use crate::formats::toml::{toml_to_json_string, toml_to_toml_string, toml_to_yaml_string, toml_to_csv_string};
-use crate::formats::yaml::{yaml_to_json_string, yaml_to_toml_string, yaml_to_yaml_string, yaml_to_csv_string};
-use crate::formats::csv::{csv_to_json_string, csv_to_toml_string, csv_to_yaml_string, csv_to_csv_string};
+use crate::document::Document;
+use crate::options::ConvertOptions;
+use crate::warning::ConversionWarning;
+use crate::formats::csv::{CsvFormat, PSV, TSV};
+use crate::formats::json::JsonFormat;
+use crate::formats::ndjson::NdjsonFormat;
+use crate::formats::toml::TomlFormat;
+use crate::formats::xml::XmlFormat;
+use crate::formats::yaml::YamlFormat;
+use crate::registry::{Capabilities, Format};
+use crate::stream::{convert_stream_buffered, convert_stream_two_pass, write_error, PendingFile, RecordWriter, Records};
 
 /// Unterstützte Dateiformate
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FileFormat {
     Json,
+    /// Ein JSON-Wert pro Zeile (JSON Lines)
+    Ndjson,
     Toml,
     Yaml,
     Csv,
+    /// Tab-getrennt (CSV-Engine)
+    Tsv,
+    /// Pipe-getrennt (CSV-Engine)
+    Psv,
+    Xml,
 }
 
 impl FileFormat {
+    /// Alle eingebauten Formate
+    pub const ALL: &'static [FileFormat] = &[
+        FileFormat::Json,
+        FileFormat::Ndjson,
+        FileFormat::Toml,
+        FileFormat::Yaml,
+        FileFormat::Csv,
+        FileFormat::Tsv,
+        FileFormat::Psv,
+        FileFormat::Xml,
+    ];
+
+    /// Die Format-Implementierung hinter diesem Enum-Wert
+    pub fn handler(&self) -> &'static dyn Format {
+        match self {
+            FileFormat::Json => &JsonFormat,
+            FileFormat::Ndjson => &NdjsonFormat,
+            FileFormat::Toml => &TomlFormat,
+            FileFormat::Yaml => &YamlFormat,
+            FileFormat::Csv => &CsvFormat,
+            FileFormat::Tsv => &TSV,
+            FileFormat::Psv => &PSV,
+            FileFormat::Xml => &XmlFormat,
+        }
+    }
+
     /// Konvertiert Input-String von diesem Format zu einem anderen Format
     /// 
     /// # Beispiel
     /// ```
+    /// use asp_cli::FileFormat;
+    ///
     /// let json_input = r#"{"name": "Test"}"#;
     /// let yaml_output = FileFormat::Json.convert(json_input, FileFormat::Yaml)?;
+    /// # Ok::<(), asp_cli::FormatError>(())
     /// ```
     pub fn convert(&self, input: &str, output_format: FileFormat) -> Result<String, FormatError> {
-        match (self, output_format) {
-            // JSON als Quelle
-            (FileFormat::Json, FileFormat::Json) => json_to_json_string(input),
-            (FileFormat::Json, FileFormat::Toml) => json_to_toml_string(input),
-            (FileFormat::Json, FileFormat::Yaml) => json_to_yaml_string(input),
-            (FileFormat::Json, FileFormat::Csv) => json_to_csv_string(input),
-            
-            // TOML als Quelle
-            (FileFormat::Toml, FileFormat::Json) => toml_to_json_string(input),
-            (FileFormat::Toml, FileFormat::Toml) => toml_to_toml_string(input),
-            (FileFormat::Toml, FileFormat::Yaml) => toml_to_yaml_string(input),
-            (FileFormat::Toml, FileFormat::Csv) => toml_to_csv_string(input),
-            
-            // YAML als Quelle
-            (FileFormat::Yaml, FileFormat::Json) => yaml_to_json_string(input),
-            (FileFormat::Yaml, FileFormat::Toml) => yaml_to_toml_string(input),
-            (FileFormat::Yaml, FileFormat::Yaml) => yaml_to_yaml_string(input),
-            (FileFormat::Yaml, FileFormat::Csv) => yaml_to_csv_string(input),
-            
-            // CSV als Quelle
-            (FileFormat::Csv, FileFormat::Json) => csv_to_json_string(input),
-            (FileFormat::Csv, FileFormat::Toml) => csv_to_toml_string(input),
-            (FileFormat::Csv, FileFormat::Yaml) => csv_to_yaml_string(input),
-            (FileFormat::Csv, FileFormat::Csv) => csv_to_csv_string(input),
+        self.convert_with(input, output_format, &ConvertOptions::default())
+    }
+    
+    /// Wie `convert`, mit Optionen für Reader und Writer (z.B. CSV-Dialekt)
+    pub fn convert_with(
+        &self,
+        input: &str,
+        output_format: FileFormat,
+        options: &ConvertOptions,
+    ) -> Result<String, FormatError> {
+        self.convert_with_warnings(input, output_format, options).map(|(output, _)| output)
+    }
+    
+    /// Wie `convert_with`, gibt zusätzlich alle Warnungen zurück
+    /// 
+    /// # Beispiel
+    /// ```
+    /// use asp_cli::{ConvertOptions, FileFormat, WarningKind};
+    ///
+    /// let (toml, warnings) = FileFormat::Json.convert_with_warnings(
+    ///     r#"[{"a": 1}]"#,
+    ///     FileFormat::Toml,
+    ///     &ConvertOptions::default(),
+    /// )?;
+    /// assert!(toml.contains("[[data]]"));
+    /// assert_eq!(warnings[0].kind, WarningKind::RootWrapped);
+    /// # Ok::<(), asp_cli::FormatError>(())
+    /// ```
+    pub fn convert_with_warnings(
+        &self,
+        input: &str,
+        output_format: FileFormat,
+        options: &ConvertOptions,
+    ) -> Result<(String, Vec<ConversionWarning>), FormatError> {
+        // Lesen → Zwischenmodell → Schreiben (TOML → TOML verlustfrei)
+        crate::warning::convert_reporting(self.handler(), output_format.handler(), input, options)
+    }
+    
+    /// Konvertiert von einem `Read` in ein `Write`, z.B. Sockets, komprimierte
+    /// Streams oder Puffer im Speicher
+    ///
+    /// NDJSON, CSV und JSON-Arrays werden Datensatz für Datensatz gelesen,
+    /// ohne die Eingabe als String zwischenzuspeichern (siehe `stream.rs`).
+    ///
+    /// # Beispiel
+    /// ```
+    /// use asp_cli::FileFormat;
+    ///
+    /// let mut output = Vec::new();
+    /// FileFormat::Csv.convert_reader("a,b\n1,2\n".as_bytes(), &mut output, FileFormat::Ndjson)?;
+    /// assert_eq!(output, b"{\"a\":1,\"b\":2}\n");
+    /// # Ok::<(), asp_cli::FormatError>(())
+    /// ```
+    pub fn convert_reader(&self, input: impl Read, output: impl Write, output_format: FileFormat) -> Result<(), FormatError> {
+        self.convert_reader_with(input, output, output_format, &ConvertOptions::default()).map(|_| ())
+    }
+
+    /// Wie `convert_reader`, mit Optionen und den Warnungen der Konvertierung
+    ///
+    /// Für CSV als Ziel werden die Datensätze gesammelt, damit die Spalten
+    /// alle Keys enthalten wie bei `convert` (ausser sie sind über
+    /// `CsvOptions::columns` festgelegt; eine Datei liest `convert_file_with`
+    /// dafür stattdessen zweimal).
+    pub fn convert_reader_with(
+        &self,
+        input: impl Read,
+        output: impl Write,
+        output_format: FileFormat,
+        options: &ConvertOptions,
+    ) -> Result<Vec<ConversionWarning>, FormatError> {
+        let mut input = BufReader::new(input);
+        let mut output = BufWriter::new(output);
+        let warnings = convert_stream_buffered(self, &output_format, &mut input, &mut output, options)?;
+        output.flush().map_err(write_error)?;
+        Ok(warnings)
+    }
+
+    /// Konvertiert eine Datei in eine andere
+    ///
+    /// # Beispiel
+    /// ```no_run
+    /// use asp_cli::FileFormat;
+    ///
+    /// FileFormat::Json.convert_file("users.json", "users.csv", FileFormat::Csv)?;
+    /// # Ok::<(), asp_cli::FormatError>(())
+    /// ```
+    pub fn convert_file(
+        &self,
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+        output_format: FileFormat,
+    ) -> Result<(), FormatError> {
+        self.convert_file_with(input, output, output_format, &ConvertOptions::default()).map(|_| ())
+    }
+
+    /// Wie `convert_file`, mit Optionen und den Warnungen der Konvertierung
+    ///
+    /// Die Eingabe wird gestreamt, bei CSV als Ziel zweimal gelesen (Spalten
+    /// aller Datensätze). Ist die Ausgabe dieselbe Datei, wird die Eingabe
+    /// zuerst komplett gelesen.
+    pub fn convert_file_with(
+        &self,
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+        output_format: FileFormat,
+        options: &ConvertOptions,
+    ) -> Result<Vec<ConversionWarning>, FormatError> {
+        let (input, output) = (input.as_ref(), output.as_ref());
+        let read_error = |e: std::io::Error| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input.display(), e));
+        let write_error = |e: std::io::Error| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output.display(), e));
+
+        let same_file = match (input.canonicalize(), output.canonicalize()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        };
+        if same_file {
+            let content = std::fs::read_to_string(input).map_err(read_error)?;
+            let (result, warnings) = self.convert_with_warnings(&content, output_format, options)?;
+            std::fs::write(output, result).map_err(write_error)?;
+            return Ok(warnings);
         }
+
+        // Erst nach Erfolg ersetzen, ein Fehler lässt die alte Ausgabe stehen
+        let mut reader = BufReader::new(File::open(input).map_err(read_error)?);
+        let mut writer = PendingFile::create(output).map_err(write_error)?;
+        let warnings = convert_stream_two_pass(self, &output_format, &mut reader, &mut writer, options)?;
+        writer.commit().map_err(write_error)?;
+        Ok(warnings)
+    }
+
+    /// Liest das Zwischenmodell aus einem `Read`
+    pub fn read_from(&self, mut reader: impl Read) -> Result<Document, FormatError> {
+        let mut content = String::new();
+        reader
+            .read_to_string(&mut content)
+            .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen der Eingabe: {}", e)))?;
+        self.read(&content)
+    }
+
+    /// Schreibt ein Dokument in diesem Format in ein `Write`
+    pub fn write_to(&self, document: &Document, mut writer: impl Write) -> Result<(), FormatError> {
+        writer.write_all(self.write(document)?.as_bytes()).map_err(write_error)
+    }
+
+    /// Liest einen Input-String dieses Formats in das Zwischenmodell
+    pub fn read(&self, input: &str) -> Result<Document, FormatError> {
+        self.handler().read(input)
+    }
+    
+    /// Schreibt ein Dokument in diesem Format
+    pub fn write(&self, document: &Document) -> Result<String, FormatError> {
+        self.handler().write(document)
     }
     
     /// Gibt den Format-Namen als String zurück
     pub fn as_str(&self) -> &'static str {
-        match self {
-            FileFormat::Json => "json",
-            FileFormat::Toml => "toml",
-            FileFormat::Yaml => "yaml",
-            FileFormat::Csv => "csv",
-        }
+        self.handler().name()
     }
     
     /// Gibt die Standard-Dateierweiterung zurück
     pub fn extension(&self) -> &'static str {
-        self.as_str()
+        self.handler().extensions()[0]
+    }
+}
+
+/// Eingebaute Formate sind ebenfalls registrierbare Formate (siehe `FormatRegistry::default`)
+impl Format for FileFormat {
+    fn name(&self) -> &str {
+        self.handler().name()
+    }
+
+    fn aliases(&self) -> &[&str] {
+        self.handler().aliases()
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.handler().extensions()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.handler().capabilities()
+    }
+
+    fn read(&self, input: &str) -> Result<Document, FormatError> {
+        self.handler().read(input)
+    }
+
+    fn write(&self, document: &Document) -> Result<String, FormatError> {
+        self.handler().write(document)
+    }
+
+    fn read_with(&self, input: &str, options: &ConvertOptions) -> Result<Document, FormatError> {
+        self.handler().read_with(input, options)
+    }
+
+    fn write_with(&self, document: &Document, options: &ConvertOptions) -> Result<String, FormatError> {
+        self.handler().write_with(document, options)
+    }
+
+    fn write_with_warnings(
+        &self,
+        document: &Document,
+        options: &ConvertOptions,
+        warnings: &mut Vec<ConversionWarning>,
+    ) -> Result<String, FormatError> {
+        self.handler().write_with_warnings(document, options, warnings)
+    }
+
+    fn rewrite_same(&self, input: &str, options: &ConvertOptions) -> Option<Result<String, FormatError>> {
+        self.handler().rewrite_same(input, options)
+    }
+
+    fn read_records<'a>(&self, input: &'a mut dyn BufRead, options: &ConvertOptions) -> Option<Records<'a>> {
+        self.handler().read_records(input, options)
+    }
+
+    fn record_writer<'a>(
+        &self,
+        output: &'a mut dyn Write,
+        options: &ConvertOptions,
+    ) -> Option<Box<dyn RecordWriter + 'a>> {
+        self.handler().record_writer(output, options)
     }
 }
 
@@ -74,13 +301,12 @@ impl FromStr for FileFormat {
     type Err = FormatError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "json" => Ok(FileFormat::Json), // "json" string zu fileformat enum konvertieren
-            "toml" => Ok(FileFormat::Toml),
-            "yaml" | "yml" => Ok(FileFormat::Yaml),
-            "csv" => Ok(FileFormat::Csv),
-            _ => Err(FormatError::ParseError(format!("Unbekanntes Format: {}", s))),
-        }
+        // "json" string zu fileformat enum konvertieren (Name oder Alias wie "yml")
+        FileFormat::ALL
+            .iter()
+            .copied()
+            .find(|format| format.handler().matches_name(s))
+            .ok_or_else(|| FormatError::InvalidFormat(format!("Unbekanntes Format: {}", s)))
     }
 }
 
@@ -101,6 +327,8 @@ mod tests {
         assert_eq!("yml".parse::<FileFormat>().unwrap(), FileFormat::Yaml);
         assert_eq!("toml".parse::<FileFormat>().unwrap(), FileFormat::Toml);
         assert_eq!("CSV".parse::<FileFormat>().unwrap(), FileFormat::Csv);
+        assert_eq!("tab".parse::<FileFormat>().unwrap(), FileFormat::Tsv);
+        assert_eq!("psv".parse::<FileFormat>().unwrap(), FileFormat::Psv);
     }
 
     #[test]
@@ -117,5 +345,107 @@ mod tests {
         let result = FileFormat::Json.convert(json_input, FileFormat::Json);
         assert!(result.is_ok());
     }
-}
 
+    #[test]
+    fn test_read_keeps_types_and_order() {
+        use crate::document::Value;
+
+        let doc = FileFormat::Toml
+            .read("zeta = 1\nalpha = 1979-05-27T07:32:00Z\n")
+            .unwrap();
+        let map = doc.root.as_map().unwrap();
+        let keys: Vec<_> = map.iter().map(|(k, _)| k.to_key_string()).collect();
+        assert_eq!(keys, vec!["zeta", "alpha"]);
+        assert!(matches!(map.get("alpha").unwrap().value, Value::DateTime(_)));
+        assert_eq!(map.get("zeta").unwrap().span.map(|s| s.line), Some(1));
+    }
+
+    #[test]
+    fn test_datetimes_survive_round_trips() {
+        use crate::formats::json::{JsonDateTime, JsonOptions};
+
+        let toml = "odt = 1979-05-27T07:32:00-07:00\nlt = 07:32:00\ns = \"2024-01-01\"\n";
+        let yaml = FileFormat::Toml.convert(toml, FileFormat::Yaml).unwrap();
+        assert_eq!(yaml, "odt: 1979-05-27T07:32:00-07:00\nlt: '07:32:00'\ns: '2024-01-01'\n");
+        // Reine Uhrzeiten kennt YAML nicht, sie kommen als String zurück
+        assert_eq!(
+            FileFormat::Yaml.convert(&yaml, FileFormat::Toml).unwrap(),
+            "odt = 1979-05-27T07:32:00-07:00\nlt = \"07:32:00\"\ns = \"2024-01-01\"\n"
+        );
+
+        let tagged = ConvertOptions { json: JsonOptions { datetime: JsonDateTime::Tagged }, ..ConvertOptions::default() };
+        let json = FileFormat::Toml.convert_with(toml, FileFormat::Json, &tagged).unwrap();
+        assert!(json.contains(r#""$datetime": "07:32:00""#));
+        assert_eq!(FileFormat::Json.convert_with(&json, FileFormat::Toml, &tagged).unwrap(), toml);
+
+        // Ohne die Option ist `$datetime` ein ganz normaler Key
+        let literal = "{\n  \"at\": {\n    \"$datetime\": \"1979-05-27T07:32:00Z\"\n  }\n}";
+        assert_eq!(FileFormat::Json.convert(literal, FileFormat::Json).unwrap(), literal);
+    }
+
+    #[test]
+    fn test_big_numbers_survive_round_trips() {
+        let json = "{\n  \"id\": 18446744073709551616,\n  \"price\": 0.12345678901234567890\n}";
+        for format in [FileFormat::Yaml, FileFormat::Csv] {
+            let text = FileFormat::Json.convert(json, format).unwrap();
+            assert!(text.contains("18446744073709551616"));
+            let back = format.convert(&text, FileFormat::Json).unwrap();
+            assert!(back.contains("0.12345678901234567890"), "{}: {}", format, back);
+        }
+    }
+
+    #[test]
+    fn test_exact_floats_stay_floats() {
+        let json = r#"{"sum": 0.30000000000000004, "pi": 3.141592653589793}"#;
+        let (toml, warnings) = FileFormat::Json.convert_with_warnings(json, FileFormat::Toml, &ConvertOptions::default()).unwrap();
+        assert_eq!(toml, "sum = 0.30000000000000004\npi = 3.141592653589793\n");
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn test_yaml_tags_and_non_string_keys() {
+        let yaml = FileFormat::Yaml.convert("1: !Ref vpc\n", FileFormat::Yaml).unwrap();
+        assert_eq!(yaml, "1: !Ref vpc\n");
+
+        let json = FileFormat::Yaml.convert("1: true\n", FileFormat::Json).unwrap();
+        assert!(json.contains(r#""1": true"#));
+    }
+
+    #[test]
+    fn test_parse_error_has_location() {
+        let err = FileFormat::Json.convert("{\n  \"a\": }", FileFormat::Yaml).unwrap_err();
+        let span = err.span().expect("Position erwartet");
+        assert_eq!((span.line, span.column), (2, 8));
+        assert!(matches!(err, FormatError::ParseError { ref format, .. } if format == "json"));
+    }
+
+    #[test]
+    fn test_reader_and_file_apis() {
+        let input = "[{\"a\": 1}, {\"a\": 2, \"b\": 3}]";
+        let expected = FileFormat::Json.convert(input, FileFormat::Csv).unwrap();
+
+        // gestückelte Eingabe wie von einem Socket
+        let chunks = std::io::Read::chain(&input.as_bytes()[..7], &input.as_bytes()[7..]);
+        let mut output = Vec::new();
+        FileFormat::Json.convert_reader(chunks, &mut output, FileFormat::Ndjson).unwrap();
+        assert_eq!(output, b"{\"a\":1}\n{\"a\":2,\"b\":3}\n");
+
+        // CSV-Spalten aus allen Datensätzen, wie bei `convert`
+        let mut output = Vec::new();
+        FileFormat::Json.convert_reader(input.as_bytes(), &mut output, FileFormat::Csv).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+        let dir = std::env::temp_dir().join(format!("asp_format_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (json, csv) = (dir.join("in.json"), dir.join("out.csv"));
+        std::fs::write(&json, input).unwrap();
+        FileFormat::Json.convert_file(&json, &csv, FileFormat::Csv).unwrap();
+        FileFormat::Json.convert_file(&json, &json, FileFormat::Yaml).unwrap();
+        let written = std::fs::read_to_string(&csv).unwrap();
+        let in_place = std::fs::read_to_string(&json).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(written, expected);
+        assert_eq!(in_place, FileFormat::Json.convert(input, FileFormat::Yaml).unwrap());
+    }
+}
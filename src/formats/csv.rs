@@ -1,192 +1,1395 @@
 // CSV zu anderen Formaten konvertieren
 
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::path::Path;
+use std::io::{BufRead, Chain, Cursor, Read, Write};
+use std::str::FromStr;
+use crate::document::{parse_number, scalar_to_string, Document, Map, Node, Span, Value};
 use crate::error::FormatError;
-use crate::formats::utils::json_to_toml_value;
-use csv::ReaderBuilder;
-use serde_json::Value as JsonValue;
+use crate::options::ConvertOptions;
+use crate::registry::{Capabilities, Format};
+use crate::stream::{write_error, RecordWriter, Records};
+use crate::warning::{pointer_push, ConversionWarning, WarningKind};
+use crate::formats::json::{write_json, JsonNode, JsonOptions};
+use crate::formats::toml::write_toml;
+use crate::formats::utils::LineIndex;
+use crate::formats::yaml::write_yaml;
+use csv::{ReaderBuilder, WriterBuilder};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, SeqAccess, Unexpected, Visitor};
 
 // ============================================================================
-// STRING-ZU-STRING FUNKTIONEN (Core-Logik für CLI und Web)
+// FORMAT-DEFINITION (für die FormatRegistry)
 // ============================================================================
 
-/// Konvertiert CSV String zu JSON String
-pub fn csv_to_json_string(input: &str) -> Result<String, FormatError> {
-    let records = parse_csv_to_json_values(input)?;
-    let json_value = JsonValue::Array(records);
-    
-    serde_json::to_string_pretty(&json_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von JSON: {}", e)))
+/// CSV als registrierbares Format
+pub struct CsvFormat;
+
+impl Format for CsvFormat {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["csv"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: true,
+            write: true,
+            nested: false,
+            root_array: true,
+            null: false,
+            datetime: false,
+            tags: false,
+            aliases: false,
+        }
+    }
+
+    fn read(&self, input: &str) -> Result<Document, FormatError> {
+        read_csv(input)
+    }
+
+    fn write(&self, document: &Document) -> Result<String, FormatError> {
+        write_csv(document)
+    }
+
+    fn read_with(&self, input: &str, options: &ConvertOptions) -> Result<Document, FormatError> {
+        read_csv_with(input, &options.csv)
+    }
+
+    fn write_with(&self, document: &Document, options: &ConvertOptions) -> Result<String, FormatError> {
+        write_csv_rows(document, &options.csv, options.sort_keys, &mut Vec::new())
+    }
+
+    fn write_with_warnings(
+        &self,
+        document: &Document,
+        options: &ConvertOptions,
+        warnings: &mut Vec<ConversionWarning>,
+    ) -> Result<String, FormatError> {
+        let output = write_csv_rows(document, &options.csv, options.sort_keys, warnings)?;
+        collect_cell_warnings(document, &options.csv, warnings);
+        Ok(output)
+    }
+
+    fn read_records<'a>(&self, input: &'a mut dyn BufRead, options: &ConvertOptions) -> Option<Records<'a>> {
+        Some(Box::new(CsvRecords::new(input, &options.csv)))
+    }
+
+    fn record_writer<'a>(
+        &self,
+        output: &'a mut dyn Write,
+        options: &ConvertOptions,
+    ) -> Option<Box<dyn RecordWriter + 'a>> {
+        Some(Box::new(CsvRecordWriter::new(output, &options.csv, options.sort_keys)))
+    }
 }
 
-/// Konvertiert CSV String zu YAML String
-pub fn csv_to_yaml_string(input: &str) -> Result<String, FormatError> {
-    let records = parse_csv_to_json_values(input)?;
-    let json_value = JsonValue::Array(records);
-    
-    serde_yaml::to_string(&json_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von YAML: {}", e)))
+/// CSV-Variante mit festem Trennzeichen (z.B. TSV)
+///
+/// Liest und schreibt über die CSV-Engine, das Trennzeichen aus den
+/// Optionen wird dabei überschrieben.
+pub struct DelimitedFormat {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub extensions: &'static [&'static str],
+    pub delimiter: u8,
 }
 
-/// Konvertiert CSV String zu TOML String
-pub fn csv_to_toml_string(input: &str) -> Result<String, FormatError> {
-    let records = parse_csv_to_json_values(input)?;
-    let json_array = JsonValue::Array(records);
-    
-    // TOML braucht ein Objekt als Root
-    let mut root_object = serde_json::Map::new();
-    root_object.insert("data".to_string(), json_array);
-    let json_value = JsonValue::Object(root_object);
-    
-    let toml_value = json_to_toml_value(&json_value)?;
-    
-    toml::to_string_pretty(&toml_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von TOML: {}", e)))
+/// Tab-getrennte Werte (`.tsv`, `.tab`)
+pub const TSV: DelimitedFormat = DelimitedFormat {
+    name: "tsv",
+    aliases: &["tab"],
+    extensions: &["tsv", "tab"],
+    delimiter: b'\t',
+};
+
+/// Pipe-getrennte Werte (`.psv`)
+pub const PSV: DelimitedFormat = DelimitedFormat {
+    name: "psv",
+    aliases: &[],
+    extensions: &["psv"],
+    delimiter: b'|',
+};
+
+impl DelimitedFormat {
+    fn csv_options(&self, options: &CsvOptions) -> CsvOptions {
+        CsvOptions { delimiter: Some(self.delimiter), ..options.clone() }
+    }
 }
 
-/// Konvertiert CSV String zu CSV String (Formatierung)
-pub fn csv_to_csv_string(input: &str) -> Result<String, FormatError> {
-    let records = parse_csv_to_json_values(input)?;
-    
-    if records.is_empty() {
+impl Format for DelimitedFormat {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn aliases(&self) -> &[&str] {
+        self.aliases
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        CsvFormat.capabilities()
+    }
+
+    fn read(&self, input: &str) -> Result<Document, FormatError> {
+        self.read_with(input, &ConvertOptions::default())
+    }
+
+    fn write(&self, document: &Document) -> Result<String, FormatError> {
+        self.write_with(document, &ConvertOptions::default())
+    }
+
+    fn read_with(&self, input: &str, options: &ConvertOptions) -> Result<Document, FormatError> {
+        read_csv_with(input, &self.csv_options(&options.csv))
+    }
+
+    fn write_with(&self, document: &Document, options: &ConvertOptions) -> Result<String, FormatError> {
+        write_csv_rows(document, &self.csv_options(&options.csv), options.sort_keys, &mut Vec::new())
+    }
+
+    fn write_with_warnings(
+        &self,
+        document: &Document,
+        options: &ConvertOptions,
+        warnings: &mut Vec<ConversionWarning>,
+    ) -> Result<String, FormatError> {
+        let output = write_csv_rows(document, &self.csv_options(&options.csv), options.sort_keys, warnings)?;
+        collect_cell_warnings(document, &options.csv, warnings);
+        Ok(output)
+    }
+
+    fn read_records<'a>(&self, input: &'a mut dyn BufRead, options: &ConvertOptions) -> Option<Records<'a>> {
+        Some(Box::new(CsvRecords::new(input, &self.csv_options(&options.csv))))
+    }
+
+    fn record_writer<'a>(
+        &self,
+        output: &'a mut dyn Write,
+        options: &ConvertOptions,
+    ) -> Option<Box<dyn RecordWriter + 'a>> {
+        Some(Box::new(CsvRecordWriter::new(output, &self.csv_options(&options.csv), options.sort_keys)))
+    }
+}
+
+// ============================================================================
+// DIALEKT-OPTIONEN
+// ============================================================================
+
+/// CSV-Dialekt für Lesen und Schreiben
+///
+/// Der Standard entspricht RFC 4180 mit Header-Zeile. Ohne festes
+/// Trennzeichen wird es beim Lesen aus dem Inhalt erkannt (z.B. Semikolon
+/// bei Excel-Exporten), beim Schreiben wird ein Komma verwendet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    /// Trennzeichen, `None` = beim Lesen erkennen, beim Schreiben `,`
+    pub delimiter: Option<u8>,
+    /// Anführungszeichen für Felder
+    pub quote: u8,
+    /// Escape-Zeichen für Anführungszeichen (z.B. `\`), nur ohne `double_quote` beim Schreiben relevant
+    pub escape: Option<u8>,
+    /// Anführungszeichen im Feld werden verdoppelt (`""`)
+    pub double_quote: bool,
+    /// Zeilen, die mit diesem Zeichen beginnen, werden beim Lesen ignoriert
+    pub comment: Option<u8>,
+    /// Zeilen mit unterschiedlich vielen Feldern erlauben
+    pub flexible: bool,
+    /// Erste Zeile enthält die Spaltennamen; ohne Header wird jede Zeile ein Array
+    pub has_headers: bool,
+    /// Zeilenende beim Schreiben
+    pub terminator: LineTerminator,
+    /// Spalten beim Schreiben (Auswahl und Reihenfolge), sonst aus den Keys
+    /// aller Datensätze. Beim Streamen von stdin nötig, damit auch später
+    /// auftauchende Keys eine Spalte haben.
+    pub columns: Option<Vec<String>>,
+    /// Verschachtelte Objekte und Arrays beim Schreiben auf Spalten verteilen
+    pub flatten: FlattenOptions,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: None,
+            quote: b'"',
+            escape: None,
+            double_quote: true,
+            comment: None,
+            flexible: false,
+            has_headers: true,
+            terminator: LineTerminator::Lf,
+            columns: None,
+            flatten: FlattenOptions::default(),
+        }
+    }
+}
+
+/// Zeilenende für CSV
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineTerminator {
+    /// `\n` beim Schreiben, beim Lesen werden `\n`, `\r\n` und `\r` erkannt
+    Lf,
+    /// `\r\n` beim Schreiben (z.B. für Excel), Lesen wie `Lf`
+    Crlf,
+    /// Beliebiges Byte als Zeilenende, beim Lesen und Schreiben
+    Custom(u8),
+}
+
+/// Wie verschachtelte Daten beim Schreiben auf CSV-Spalten verteilt werden
+///
+/// Der Standard entspricht dem bisherigen Verhalten: Keys mit `_` verbunden
+/// (`user_name`), Arrays als JSON-String in einer Zelle.
+///
+/// # Beispiel
+/// ```
+/// use asp_cli::{ConvertOptions, FileFormat};
+/// use asp_cli::formats::csv::{ArrayMode, CsvOptions, FlattenOptions};
+///
+/// let options = ConvertOptions {
+///     csv: CsvOptions {
+///         flatten: FlattenOptions { separator: ".".to_string(), arrays: ArrayMode::Index, max_depth: None },
+///         ..CsvOptions::default()
+///     },
+///     ..ConvertOptions::default()
+/// };
+/// let csv = FileFormat::Json.convert_with(r#"[{"user": {"first_name": "A"}, "tags": ["x", "y"]}]"#, FileFormat::Csv, &options)?;
+/// assert_eq!(csv, "user.first_name,tags.0,tags.1\nA,x,y\n");
+/// # Ok::<(), asp_cli::FormatError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlattenOptions {
+    /// Trennzeichen zwischen den Keys einer Spalte, z.B. `_`, `.` oder `/`
+    pub separator: String,
+    /// Umgang mit Arrays in Objekt-Zeilen
+    pub arrays: ArrayMode,
+    /// Maximale Anzahl Keys pro Spaltenname; tiefere Objekte und Arrays
+    /// landen als JSON-String in einer Zelle. `None` = unbegrenzt
+    pub max_depth: Option<usize>,
+}
+
+impl Default for FlattenOptions {
+    fn default() -> Self {
+        FlattenOptions { separator: "_".to_string(), arrays: ArrayMode::Json, max_depth: None }
+    }
+}
+
+impl FlattenOptions {
+    /// Spaltenname für `key` unterhalb von `prefix`
+    fn column(&self, prefix: &str, key: &str) -> String {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}{}{}", prefix, self.separator, key)
+        }
+    }
+
+    /// Darf ein Wert in einer Spalte mit `depth` Keys weiter aufgeteilt werden?
+    fn expands(&self, depth: usize) -> bool {
+        self.max_depth.is_none_or(|max| depth < max)
+    }
+}
+
+/// Arrays in CSV-Zellen
+///
+/// Als Text (CLI): `json`, `index`, `join`, `join:<TEXT>` oder `explode`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ArrayMode {
+    /// Ganzes Array als JSON-String in einer Zelle (`["a","b"]`)
+    #[default]
+    Json,
+    /// Eine Spalte pro Eintrag (`tags_0`, `tags_1`)
+    Index,
+    /// Einträge mit diesem Text verbunden in einer Zelle (`a;b`)
+    Join(String),
+    /// Eine Zeile pro Eintrag, die übrigen Spalten werden wiederholt.
+    /// Mehrere Arrays in einem Datensatz ergeben alle Kombinationen.
+    Explode,
+}
+
+impl FromStr for ArrayMode {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ArrayMode::Json),
+            "index" => Ok(ArrayMode::Index),
+            "join" => Ok(ArrayMode::Join(";".to_string())),
+            "explode" => Ok(ArrayMode::Explode),
+            _ => match s.strip_prefix("join:") {
+                Some(text) => Ok(ArrayMode::Join(text.to_string())),
+                None => Err(FormatError::InvalidFormat(format!(
+                    "Unbekannte Array-Darstellung: {} (erlaubt: json, index, join, join:<TEXT>, explode)",
+                    s
+                ))),
+            },
+        }
+    }
+}
+
+impl CsvOptions {
+    fn reader_builder(&self, input: &str) -> ReaderBuilder {
+        let mut builder = ReaderBuilder::new();
+        builder
+            .delimiter(self.delimiter.unwrap_or_else(|| sniff_delimiter_with(input, self.quote)))
+            .quote(self.quote)
+            .escape(self.escape)
+            .double_quote(self.double_quote)
+            .comment(self.comment)
+            .flexible(self.flexible)
+            .has_headers(self.has_headers)
+            .terminator(match self.terminator {
+                LineTerminator::Custom(byte) => csv::Terminator::Any(byte),
+                // CRLF akzeptiert beim Lesen alle Varianten
+                LineTerminator::Lf | LineTerminator::Crlf => csv::Terminator::CRLF,
+            });
+        builder
+    }
+
+    fn writer_builder(&self) -> WriterBuilder {
+        let mut builder = WriterBuilder::new();
+        builder
+            .delimiter(self.delimiter.unwrap_or(b','))
+            .quote(self.quote)
+            .double_quote(self.double_quote)
+            .flexible(self.flexible)
+            .terminator(match self.terminator {
+                LineTerminator::Lf => csv::Terminator::Any(b'\n'),
+                LineTerminator::Crlf => csv::Terminator::CRLF,
+                LineTerminator::Custom(byte) => csv::Terminator::Any(byte),
+            });
+        if let Some(escape) = self.escape {
+            builder.escape(escape);
+        }
+        builder
+    }
+}
+
+/// Kandidaten für die Erkennung, bei Gleichstand gewinnt der vordere
+const DELIMITER_CANDIDATES: &[u8] = b",;\t|";
+
+/// So viele Zeilen werden für die Erkennung angeschaut
+const SNIFF_LINES: usize = 20;
+
+/// Beim Streamen wird höchstens so viel vorab gelesen (ausser die erste
+/// Zeile ist länger)
+const SNIFF_BYTES: usize = 64 * 1024;
+
+/// Erkennt das Trennzeichen anhand der ersten Zeilen
+///
+/// Gewinner ist das Zeichen, das in jeder Zeile gleich oft (und am
+/// häufigsten) ausserhalb von Anführungszeichen vorkommt. Ohne klaren
+/// Treffer bleibt es beim Komma.
+///
+/// ```
+/// use asp_cli::formats::csv::sniff_delimiter;
+///
+/// assert_eq!(sniff_delimiter("name;preis\nKaffee;3,50\nTee;2,80\n"), b';');
+/// assert_eq!(sniff_delimiter("a\tb\n1\t2\n"), b'\t');
+/// ```
+pub fn sniff_delimiter(sample: &str) -> u8 {
+    sniff_delimiter_with(sample, b'"')
+}
+
+/// Wie `sniff_delimiter`, mit eigenem Anführungszeichen (`CsvOptions::quote`)
+///
+/// Zeilenumbrüche innerhalb von Anführungszeichen beenden die Zeile nicht.
+///
+/// ```
+/// use asp_cli::formats::csv::sniff_delimiter_with;
+///
+/// assert_eq!(sniff_delimiter_with("'a;b',c\n'1;2',3\n", b'\''), b',');
+/// ```
+pub fn sniff_delimiter_with(sample: &str, quote: u8) -> u8 {
+    let lines = sample_records(sample, quote);
+
+    let mut best = (b',', 0, false);
+    for &candidate in DELIMITER_CANDIDATES {
+        let counts: Vec<usize> = lines.iter().map(|line| count_unquoted(line, candidate, quote)).collect();
+        let Some(&first) = counts.first() else {
+            break;
+        };
+        let min = counts.iter().copied().min().unwrap_or(0);
+        if min == 0 {
+            continue;
+        }
+        let consistent = counts.iter().all(|&c| c == first);
+        // Konsistenz schlägt Häufigkeit
+        if (consistent, min) > (best.2, best.1) {
+            best = (candidate, min, consistent);
+        }
+    }
+    best.0
+}
+
+/// Die ersten nicht-leeren Datensätze der Probe (ohne Zeilenumbruch)
+fn sample_records(sample: &str, quote: u8) -> Vec<&str> {
+    let mut records = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, b) in sample.bytes().enumerate() {
+        if records.len() == SNIFF_LINES {
+            return records;
+        }
+        if b == quote {
+            quoted = !quoted;
+        } else if b == b'\n' && !quoted {
+            if !sample[start..i].trim().is_empty() {
+                records.push(&sample[start..i]);
+            }
+            start = i + 1;
+        }
+    }
+    // Letzter Datensatz ohne Zeilenumbruch, nur wenn er vollständig ist
+    if records.len() < SNIFF_LINES && !quoted && !sample[start..].trim().is_empty() {
+        records.push(&sample[start..]);
+    }
+    records
+}
+
+/// Liest die ersten Zeilen für `sniff_delimiter_with` vorab
+///
+/// Es werden nur ganze Zeilen gelesen, und ein Zeilenumbruch in
+/// Anführungszeichen beendet den Datensatz nicht. `SNIFF_BYTES` begrenzt
+/// den Puffer, die erste Zeile wird aber immer ganz gelesen.
+fn read_sample(input: &mut dyn BufRead, quote: u8) -> Vec<u8> {
+    let mut sample = Vec::new();
+    let mut quoted = false;
+    let mut lines = 0;
+    while lines < SNIFF_LINES && sample.len() < SNIFF_BYTES {
+        let start = sample.len();
+        match input.read_until(b'\n', &mut sample) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        if sample[start..].iter().filter(|&&b| b == quote).count() % 2 == 1 {
+            quoted = !quoted;
+        }
+        if !quoted {
+            lines += 1;
+        }
+    }
+    sample
+}
+
+/// Zählt ein Zeichen ausserhalb von Anführungszeichen
+fn count_unquoted(line: &str, needle: u8, quote: u8) -> usize {
+    let mut quoted = false;
+    line.bytes()
+        .filter(|&b| {
+            if b == quote {
+                quoted = !quoted;
+            }
+            !quoted && b == needle
+        })
+        .count()
+}
+
+// ============================================================================
+// READER / WRITER (Zwischenmodell)
+// ============================================================================
+
+/// Liest einen CSV String in das Zwischenmodell (Array von flachen Objekten)
+pub fn read_csv(input: &str) -> Result<Document, FormatError> {
+    read_csv_with(input, &CsvOptions::default())
+}
+
+/// Wie `read_csv`, mit eigenem Dialekt
+///
+/// Ohne Header wird jede Zeile ein Array statt eines Objekts.
+pub fn read_csv_with(input: &str, options: &CsvOptions) -> Result<Document, FormatError> {
+    let records = parse_csv_records(input, false, options)?;
+    Ok(Document::new(Node::array(records)))
+}
+
+/// Schreibt das Zwischenmodell als CSV String
+///
+/// Jedes Objekt wird eine Zeile, verschachtelte Objekte werden mit
+/// Unterstrich-Trennzeichen geflattened, Arrays als JSON-String abgelegt
+/// (einstellbar über `CsvOptions::flatten`).
+/// Die Spalten stehen in der Reihenfolge, in der die Keys zuerst vorkommen.
+pub fn write_csv(doc: &Document) -> Result<String, FormatError> {
+    write_csv_with(doc, &CsvOptions::default())
+}
+
+/// Wie `write_csv`, mit eigenem Dialekt
+///
+/// Besteht das Dokument nur aus Arrays (z.B. CSV ohne Header gelesen),
+/// wird jedes Array direkt eine Zeile.
+pub fn write_csv_with(doc: &Document, options: &CsvOptions) -> Result<String, FormatError> {
+    write_csv_rows(doc, options, false, &mut Vec::new())
+}
+
+/// `sort_headers`: Spalten alphabetisch statt in Quell-Reihenfolge (`--sort-keys`)
+///
+/// In `warnings` landen doppelt belegte Spalten (siehe `insert_cell`).
+fn write_csv_rows(
+    doc: &Document,
+    options: &CsvOptions,
+    sort_headers: bool,
+    warnings: &mut Vec<ConversionWarning>,
+) -> Result<String, FormatError> {
+    let rows = csv_rows(doc)?;
+
+    if rows.is_empty() {
         return Ok(String::new());
     }
-    
-    // Header aus erstem Record extrahieren
-    let headers: Vec<String> = if let JsonValue::Object(obj) = &records[0] {
-        obj.keys().cloned().collect()
-    } else {
-        return Err(FormatError::SerializationError("CSV Records müssen Objekte sein".to_string()));
-    };
-    
+
     // CSV Writer in Memory
-    let mut writer = csv::Writer::from_writer(vec![]);
-    
+    let mut writer = options.writer_builder().from_writer(vec![]);
+
+    // Zeilen als Arrays: ohne Header direkt schreiben
+    if rows.iter().all(|(_, row)| row.as_array().is_some()) {
+        for (_, row) in &rows {
+            let cells: Vec<String> = row.as_array().into_iter().flatten().map(cell_to_string).collect();
+            writer.write_record(&cells)
+                .map_err(|e| FormatError::SerializationError(format!("Fehler beim Schreiben der CSV-Zeile: {}", e)))?;
+        }
+        return finish_writer(writer);
+    }
+
+    // Alle Objekte flattenen (mit Pfad der Quellzeile für Warnungen)
+    let flattened: Vec<(&str, Vec<(String, String)>)> = rows
+        .iter()
+        .flat_map(|(path, row)| flatten_node(row, &options.flatten).into_iter().map(move |flat| (path.as_str(), flat)))
+        .collect();
+
+    // Header sammeln (Reihenfolge des ersten Vorkommens), ausser sie sind vorgegeben
+    let headers = match &options.columns {
+        Some(columns) => columns.clone(),
+        None => {
+            let mut scan = HeaderScan::default();
+            flattened.iter().for_each(|(_, obj)| scan.add(obj));
+            scan.finish(sort_headers)
+        }
+    };
+
     // Header schreiben
-    writer.write_record(&headers)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Schreiben der CSV-Header: {}", e)))?;
-    
-    // Records schreiben
-    for record in &records {
-        if let JsonValue::Object(obj) = record {
-            let row: Vec<String> = headers.iter()
-                .map(|h| {
-                    obj.get(h)
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("")
-                        .to_string()
-                })
-                .collect();
-            writer.write_record(&row)
+    if options.has_headers {
+        writer.write_record(&headers)
+            .map_err(|e| FormatError::SerializationError(format!("Fehler beim Schreiben der CSV-Header: {}", e)))?;
+    }
+
+    // Daten schreiben
+    for (path, flat_obj) in flattened {
+        let mut cells: HashMap<String, String> = HashMap::with_capacity(flat_obj.len());
+        for (key, value) in flat_obj {
+            insert_cell(&mut cells, key, value, &headers, path, warnings);
+        }
+        let row: Vec<&str> = headers.iter()
+            .map(|h| cells.get(h).map(String::as_str).unwrap_or_default())
+            .collect();
+        writer.write_record(&row)
+            .map_err(|e| FormatError::SerializationError(format!("Fehler beim Schreiben der CSV-Zeile: {}", e)))?;
+    }
+
+    finish_writer(writer)
+}
+
+/// Legt eine Zelle ab; ist die Spalte schon belegt (z.B. `{"a_b": 1, "a": {"b": 2}}`
+/// → zweimal `a_b`), gewinnt der spätere Wert und es gibt eine Warnung, sofern
+/// die Spalte geschrieben wird
+fn insert_cell(
+    cells: &mut HashMap<String, String>,
+    key: String,
+    value: String,
+    headers: &[String],
+    path: &str,
+    warnings: &mut Vec<ConversionWarning>,
+) {
+    if cells.contains_key(&key) && headers.contains(&key) {
+        let warning = ConversionWarning::new(
+            WarningKind::ColumnCollision,
+            path,
+            format!("Spalte {} mehrfach belegt, frühere Werte werden überschrieben", key),
+        );
+        // Explodierte Arrays wiederholen die Kollision in jeder Zeile
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+    cells.insert(key, value);
+}
+
+/// Zeilen für die CSV-Ausgabe, jeweils mit JSON-Pointer für Warnungen
+fn csv_rows(doc: &Document) -> Result<Vec<(String, &Node)>, FormatError> {
+    fn indexed<'a>(base: &str, items: &'a [Node]) -> Vec<(String, &'a Node)> {
+        items.iter().enumerate().map(|(i, item)| (pointer_push(base, &i.to_string()), item)).collect()
+    }
+    match &doc.root.value {
+        Value::Array(items) => Ok(indexed("", items)),
+        // Ein einzelnes Objekt wird zu einer Zeile (verpackte Root-Arrays aus
+        // TOML packt read_toml bereits aus)
+        Value::Map(_) => Ok(vec![(String::new(), &doc.root)]),
+        other => Err(FormatError::SerializationError(format!(
+            "CSV benötigt ein Array oder Objekt, gefunden: {}",
+            other.type_name()
+        ))),
+    }
+}
+
+/// Meldet Arrays und Objekte, die als JSON-String in einer Zelle landen
+///
+/// Was `CsvOptions::flatten` auf eigene Spalten (bzw. Zeilen) verteilt, ist
+/// kein Verlust. Mit `columns` zählen nur Zellen in diesen Spalten.
+fn collect_cell_warnings(doc: &Document, options: &CsvOptions, warnings: &mut Vec<ConversionWarning>) {
+    let Ok(rows) = csv_rows(doc) else { return };
+    let all_arrays = rows.iter().all(|(_, row)| row.as_array().is_some());
+    for (path, row) in rows {
+        collect_row_warnings(row, &path, all_arrays, &options.flatten, options.columns.as_deref(), warnings);
+    }
+}
+
+/// Wie `collect_cell_warnings` für eine Zeile; `array_rows`: Zeilen sind Arrays
+fn collect_row_warnings(
+    row: &Node,
+    path: &str,
+    array_rows: bool,
+    flatten: &FlattenOptions,
+    columns: Option<&[String]>,
+    warnings: &mut Vec<ConversionWarning>,
+) {
+    let mut warn = |node: &Node, path: &str| {
+        warnings.push(ConversionWarning::new(
+            WarningKind::NestedInCell,
+            path,
+            format!("{} als JSON-String in CSV-Zelle geschrieben", node.value.type_name()),
+        ));
+    };
+
+    match (&row.value, array_rows) {
+        (Value::Array(items), true) => {
+            for (i, item) in items.iter().enumerate() {
+                if scalar_to_string(&item.value).is_none() {
+                    warn(item, &pointer_push(path, &i.to_string()));
+                }
+            }
+        }
+        (Value::Map(_), _) => {
+            let written = |column: &str| columns.is_none_or(|columns| columns.iter().any(|c| c == column));
+            visit_cells(row, path, "", 0, flatten, &mut |node, path, column, json| {
+                if json && written(column) {
+                    warn(node, path);
+                }
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Geht die Zellen eines Datensatzes durch, genau wie `flatten_into` sie
+/// aufteilt: `visit(knoten, pointer, spalte, als_json)` für jede Zelle bzw.
+/// jeden Eintrag eines verbundenen Arrays
+fn visit_cells(
+    node: &Node,
+    path: &str,
+    column: &str,
+    depth: usize,
+    options: &FlattenOptions,
+    visit: &mut dyn FnMut(&Node, &str, &str, bool),
+) {
+    match &node.value {
+        Value::Map(map) if column.is_empty() || options.expands(depth) => {
+            for (key, value) in map.iter() {
+                let key = key.to_key_string();
+                visit_cells(value, &pointer_push(path, &key), &options.column(column, &key), depth + 1, options, visit);
+            }
+        }
+        Value::Array(items) if !column.is_empty() => match &options.arrays {
+            ArrayMode::Index if options.expands(depth) => {
+                for (i, item) in items.iter().enumerate() {
+                    let index = i.to_string();
+                    visit_cells(item, &pointer_push(path, &index), &options.column(column, &index), depth + 1, options, visit);
+                }
+            }
+            ArrayMode::Join(_) => {
+                for (i, item) in items.iter().enumerate() {
+                    visit(item, &pointer_push(path, &i.to_string()), column, scalar_to_string(&item.value).is_none());
+                }
+            }
+            ArrayMode::Explode => {
+                for (i, item) in items.iter().enumerate() {
+                    visit_cells(item, &pointer_push(path, &i.to_string()), column, depth, options, visit);
+                }
+            }
+            _ => visit(node, path, column, true),
+        },
+        _ => visit(node, path, column, scalar_to_string(&node.value).is_none()),
+    }
+}
+
+/// Sammelt Spaltennamen in der Reihenfolge ihres ersten Vorkommens
+#[derive(Default)]
+struct HeaderScan {
+    seen: HashSet<String>,
+    headers: Vec<String>,
+}
+
+impl HeaderScan {
+    fn add(&mut self, flat: &[(String, String)]) {
+        for (key, _) in flat {
+            if !self.seen.contains(key) {
+                self.seen.insert(key.clone());
+                self.headers.push(key.clone());
+            }
+        }
+    }
+
+    fn finish(self, sort: bool) -> Vec<String> {
+        let mut headers = self.headers;
+        if sort {
+            headers.sort();
+        }
+        headers
+    }
+}
+
+/// Schreibt CSV Zeile für Zeile (siehe `Format::record_writer`)
+///
+/// Die Spalten kommen aus `CsvOptions::columns`, aus dem ersten Durchgang
+/// (`convert_stream_two_pass`) oder sonst aus dem ersten Objekt. Im letzten
+/// Fall werden Keys, die erst später auftauchen, mit Warnung ausgelassen.
+/// Fehlende Keys bleiben leer. Ist der erste Datensatz ein Array, wird jedes
+/// Array direkt eine Zeile.
+struct CsvRecordWriter<'a> {
+    writer: csv::Writer<&'a mut dyn Write>,
+    has_headers: bool,
+    sort_headers: bool,
+    flatten: FlattenOptions,
+    /// Spalten vorgegeben (`CsvOptions::columns`) - dann ohne Warnungen
+    declared: bool,
+    /// Spalten, sobald feststehend (leer bei Array-Zeilen)
+    headers: Option<Vec<String>>,
+    /// Spalten aus dem ersten Durchgang
+    scan: Option<HeaderScan>,
+    /// Spalten stehen fest (Header-Zeile ist geschrieben oder steht an)
+    started: bool,
+    /// Header-Zeile kommt vor der nächsten Zeile
+    header_due: bool,
+    /// Zeilen aus `check_record`, noch nicht geschrieben
+    pending: Vec<Vec<String>>,
+}
+
+impl<'a> CsvRecordWriter<'a> {
+    fn new(output: &'a mut dyn Write, options: &CsvOptions, sort_headers: bool) -> Self {
+        CsvRecordWriter {
+            writer: options.writer_builder().from_writer(output),
+            has_headers: options.has_headers,
+            sort_headers,
+            flatten: options.flatten.clone(),
+            declared: options.columns.is_some(),
+            headers: options.columns.clone(),
+            scan: None,
+            started: false,
+            header_due: false,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl RecordWriter for CsvRecordWriter<'_> {
+    /// Legt die Spalten fest (beim ersten Datensatz) und bereitet die Zeilen
+    /// samt Warnungen vor, `write_record` schreibt sie dann nur noch
+    fn check_record(&mut self, record: &Node, path: &str, warnings: &mut Vec<ConversionWarning>) {
+        if let Some(items) = record.as_array()
+            && !self.declared
+            && self.headers.as_ref().is_none_or(Vec::is_empty)
+        {
+            self.headers = Some(Vec::new());
+            self.pending = vec![items.iter().map(cell_to_string).collect()];
+            collect_row_warnings(record, path, true, &self.flatten, None, warnings);
+            return;
+        }
+
+        let rows = flatten_node(record, &self.flatten);
+        if self.headers.is_none() {
+            let scan = self.scan.take().unwrap_or_else(|| {
+                let mut scan = HeaderScan::default();
+                rows.iter().for_each(|flat| scan.add(flat));
+                scan
+            });
+            self.headers = Some(scan.finish(self.sort_headers));
+        }
+        if !self.started {
+            self.started = true;
+            self.header_due = self.has_headers;
+        }
+        let headers = self.headers.as_deref().unwrap_or_default();
+
+        let mut dropped = HashSet::new();
+        self.pending = rows
+            .into_iter()
+            .map(|flat| {
+                let mut cells: HashMap<String, String> = HashMap::with_capacity(flat.len());
+                for (key, value) in flat {
+                    if headers.contains(&key) {
+                        insert_cell(&mut cells, key, value, headers, path, warnings);
+                    } else if !self.declared && dropped.insert(key.clone()) {
+                        warnings.push(ConversionWarning::new(
+                            WarningKind::ColumnDropped,
+                            path,
+                            format!("Spalte {} ausgelassen (nicht im Header des ersten Datensatzes)", key),
+                        ));
+                    }
+                }
+                headers.iter().map(|h| cells.remove(h).unwrap_or_default()).collect()
+            })
+            .collect();
+        collect_row_warnings(record, path, false, &self.flatten, Some(headers), warnings);
+    }
+
+    fn write_record(&mut self, _record: &Node) -> Result<(), FormatError> {
+        if std::mem::take(&mut self.header_due) {
+            let headers = self.headers.as_deref().unwrap_or_default();
+            self.writer.write_record(headers)
+                .map_err(|e| FormatError::SerializationError(format!("Fehler beim Schreiben der CSV-Header: {}", e)))?;
+        }
+        for row in std::mem::take(&mut self.pending) {
+            self.writer.write_record(&row)
                 .map_err(|e| FormatError::SerializationError(format!("Fehler beim Schreiben der CSV-Zeile: {}", e)))?;
         }
+        Ok(())
     }
-    
-    // Writer in String umwandeln
+
+    fn finish(mut self: Box<Self>) -> Result<(), FormatError> {
+        self.writer.flush().map_err(write_error)
+    }
+
+    fn wants_scan(&self) -> bool {
+        !self.declared
+    }
+
+    fn scan_record(&mut self, record: &Node) {
+        if record.as_map().is_some() {
+            let scan = self.scan.get_or_insert_with(HeaderScan::default);
+            flatten_node(record, &self.flatten).iter().for_each(|flat| scan.add(flat));
+        }
+    }
+}
+
+/// Writer in String umwandeln
+fn finish_writer(writer: csv::Writer<Vec<u8>>) -> Result<String, FormatError> {
     let data = writer.into_inner()
         .map_err(|e| FormatError::SerializationError(format!("Fehler beim Abschliessen von CSV: {}", e)))?;
-    
+
     String::from_utf8(data)
         .map_err(|e| FormatError::SerializationError(format!("Fehler bei UTF-8 Konvertierung: {}", e)))
 }
 
-/// Hilfsfunktion: Parst CSV String zu JSON Values (flach, keine Dot-Notation)
-fn parse_csv_to_json_values(input: &str) -> Result<Vec<JsonValue>, FormatError> {
-    let mut reader = ReaderBuilder::new()
-        .has_headers(true)
-        .from_reader(input.as_bytes());
+// ============================================================================
+// STRING-ZU-STRING FUNKTIONEN (Core-Logik für CLI und Web)
+// ============================================================================
+
+/// Konvertiert CSV String zu JSON String
+pub fn csv_to_json_string(input: &str) -> Result<String, FormatError> {
+    write_json(&read_csv(input)?)
+}
+
+/// Konvertiert CSV String zu YAML String
+pub fn csv_to_yaml_string(input: &str) -> Result<String, FormatError> {
+    write_yaml(&read_csv(input)?)
+}
+
+/// Konvertiert CSV String zu TOML String
+pub fn csv_to_toml_string(input: &str) -> Result<String, FormatError> {
+    write_toml(&read_csv(input)?)
+}
+
+/// Konvertiert CSV String zu CSV String (Formatierung)
+pub fn csv_to_csv_string(input: &str) -> Result<String, FormatError> {
+    write_csv(&read_csv(input)?)
+}
+
+/// Hilfsfunktion: Parst CSV String zu Objekt-Knoten
+///
+/// Mit `nested` werden Header in Dot-Notation zu verschachtelten Objekten.
+/// Ohne Header (`has_headers: false`) wird jede Zeile ein Array.
+fn parse_csv_records(input: &str, nested: bool, options: &CsvOptions) -> Result<Vec<Node>, FormatError> {
+    let mut reader = options.reader_builder(input).from_reader(input.as_bytes());
     
-    let headers = reader.headers()
-        .map_err(|e| FormatError::ParseError(format!("Fehler beim Lesen der CSV-Header: {}", e)))?
-        .clone();
+    let lines = LineIndex::new(input);
     
-    let header_vec: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    let header_vec: Vec<String> = if options.has_headers {
+        let headers = reader.headers()
+            .map_err(|e| csv_parse_error(input, &lines, "Fehler beim Lesen der CSV-Header", e))?;
+        headers.iter().map(|h| h.to_string()).collect()
+    } else {
+        Vec::new()
+    };
     
     let mut records = Vec::new();
     
     for result in reader.records() {
         let record = result
-            .map_err(|e| FormatError::ParseError(format!("Fehler beim Lesen eines CSV-Records: {}", e)))?;
-        
-        let mut obj = serde_json::Map::new();
-        
-        for (i, field) in record.iter().enumerate() {
-            if let Some(header) = header_vec.get(i) {
-                let value = infer_type(field);
-                obj.insert(header.clone(), value);
-            }
-        }
-        
-        records.push(JsonValue::Object(obj));
+            .map_err(|e| csv_parse_error(input, &lines, "Fehler beim Lesen eines CSV-Records", e))?;
+        let span = record.position().map(|pos| lines.span(pos.byte() as usize, 0));
+        records.push(record_to_node(&record, &header_vec, nested, options.has_headers).with_span(span));
     }
     
     Ok(records)
 }
 
+/// Ein CSV-Record als Objekt (Keys aus dem Header) bzw. ohne Header als Array
+fn record_to_node(record: &csv::StringRecord, headers: &[String], nested: bool, has_headers: bool) -> Node {
+    if !has_headers {
+        return Node::array(record.iter().map(|field| Node::new(infer_type(field))).collect());
+    }
+    
+    let mut obj = Map::new();
+    for (header, field) in headers.iter().zip(record.iter()) {
+        let value = Node::new(infer_type(field));
+        if nested {
+            // Verschachtelung durch Dot-Notation erstellen
+            insert_nested_value(&mut obj, header, value);
+        } else {
+            obj.insert(Node::string(header.clone()), value);
+        }
+    }
+    Node::map(obj)
+}
+
+/// csv-Fehler → ParseError mit Position (sofern der Reader sie kennt)
+fn csv_parse_error(input: &str, lines: &LineIndex, context: &str, error: csv::Error) -> FormatError {
+    let span = error.position().map(|pos| lines.span(pos.byte() as usize, 0));
+    FormatError::parse("csv", csv_error_message(context, &error), input, span)
+}
+
+fn csv_error_message(context: &str, error: &csv::Error) -> String {
+    match error.kind() {
+        csv::ErrorKind::UnequalLengths { expected_len, len, .. } => format!(
+            "{}: {} Felder statt {}",
+            context, len, expected_len
+        ),
+        _ => format!("{}: {}", context, error),
+    }
+}
+
+/// Liest CSV Datensatz für Datensatz (siehe `Format::read_records`)
+///
+/// Das Trennzeichen wird, falls nicht vorgegeben, am ersten Puffer der
+/// Eingabe erkannt. Fehler enthalten Zeile und Offset, aber keinen Ausschnitt.
+struct CsvRecords<'a> {
+    /// Liest zuerst die Probe für die Trennzeichen-Erkennung, dann den Rest
+    reader: csv::Reader<Chain<Cursor<Vec<u8>>, &'a mut dyn BufRead>>,
+    has_headers: bool,
+    /// `None` bis die Header-Zeile gelesen ist
+    headers: Option<Vec<String>>,
+    record: csv::StringRecord,
+    /// Nach einem Fehler ist der Stream zu Ende
+    done: bool,
+}
+
+impl<'a> CsvRecords<'a> {
+    fn new(input: &'a mut dyn BufRead, options: &CsvOptions) -> Self {
+        // Nur vollständige Zeilen, sonst zählt die letzte Zeile falsch
+        let sample = if options.delimiter.is_none() { read_sample(input, options.quote) } else { Vec::new() };
+        let builder = options.reader_builder(&String::from_utf8_lossy(&sample));
+        CsvRecords {
+            reader: builder.from_reader(Cursor::new(sample).chain(input)),
+            has_headers: options.has_headers,
+            headers: None,
+            record: csv::StringRecord::new(),
+            done: false,
+        }
+    }
+
+    fn error(&mut self, context: &str, error: csv::Error) -> FormatError {
+        self.done = true;
+        let span = error.position().map(position_span);
+        FormatError::ParseError {
+            format: "csv".to_string(),
+            message: csv_error_message(context, &error),
+            span,
+            snippet: None,
+        }
+    }
+}
+
+/// Position des csv-Readers (Anfang des Records) → Span
+fn position_span(pos: &csv::Position) -> Span {
+    Span { offset: pos.byte() as usize, len: 0, line: pos.line() as usize, column: 1 }
+}
+
+impl Iterator for CsvRecords<'_> {
+    type Item = Result<Node, FormatError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.headers.is_none() {
+            let headers = if self.has_headers {
+                match self.reader.headers() {
+                    Ok(headers) => headers.iter().map(str::to_string).collect(),
+                    Err(e) => return Some(Err(self.error("Fehler beim Lesen der CSV-Header", e))),
+                }
+            } else {
+                Vec::new()
+            };
+            self.headers = Some(headers);
+        }
+
+        match self.reader.read_record(&mut self.record) {
+            Ok(false) => None,
+            Ok(true) => {
+                let span = self.record.position().map(position_span);
+                let headers = self.headers.as_deref().unwrap_or_default();
+                Some(Ok(record_to_node(&self.record, headers, false, self.has_headers).with_span(span)))
+            }
+            Err(e) => Some(Err(self.error("Fehler beim Lesen eines CSV-Records", e))),
+        }
+    }
+}
+
+/// Flattened einen Knoten zu Zeilen aus Spalten
+///
+/// Beispiel (Standard-Optionen):
+/// Input:  {"contact": {"email": "test@test.com", "phone": "+49"}}
+/// Output: [[("contact_email", "test@test.com"), ("contact_phone", "+49")]]
+///
+/// Mehr als eine Zeile gibt es nur mit `ArrayMode::Explode`.
+fn flatten_node(node: &Node, options: &FlattenOptions) -> Vec<Vec<(String, String)>> {
+    let mut rows = vec![Vec::new()];
+    flatten_into(node, "", 0, options, &mut rows);
+    rows
+}
+
+/// `depth`: Anzahl Keys in `prefix`; jede Zelle wird an alle `rows` angehängt
+fn flatten_into(node: &Node, prefix: &str, depth: usize, options: &FlattenOptions, rows: &mut Vec<Vec<(String, String)>>) {
+    let push = |rows: &mut Vec<Vec<(String, String)>>, cell: String| {
+        for row in rows.iter_mut() {
+            row.push((prefix.to_string(), cell.clone()));
+        }
+    };
+
+    match &node.value {
+        // Rekursiv für verschachtelte Objekte (bis max_depth)
+        Value::Map(map) if prefix.is_empty() || options.expands(depth) => {
+            for (key, val) in map.iter() {
+                let column = options.column(prefix, &key.to_key_string());
+                flatten_into(val, &column, depth + 1, options, rows);
+            }
+        }
+        Value::Array(items) if !prefix.is_empty() => match &options.arrays {
+            ArrayMode::Index if options.expands(depth) => {
+                for (i, item) in items.iter().enumerate() {
+                    flatten_into(item, &options.column(prefix, &i.to_string()), depth + 1, options, rows);
+                }
+            }
+            ArrayMode::Join(delimiter) => {
+                let cell = items.iter().map(cell_to_string).collect::<Vec<_>>().join(delimiter);
+                push(rows, cell);
+            }
+            ArrayMode::Explode if items.is_empty() => push(rows, String::new()),
+            ArrayMode::Explode => {
+                // Jede bisherige Zeile einmal pro Eintrag
+                for row in std::mem::take(rows) {
+                    for item in items {
+                        let mut exploded = vec![row.clone()];
+                        flatten_into(item, prefix, depth, options, &mut exploded);
+                        rows.extend(exploded);
+                    }
+                }
+            }
+            _ => push(rows, cell_to_string(node)),
+        },
+        // Primitive Werte (und Verschachtelung ab max_depth) direkt einfügen
+        _ if !prefix.is_empty() => push(rows, cell_to_string(node)),
+        _ => {}
+    }
+}
+
+/// Konvertiert einen Knoten zu einem Zellen-String
+fn cell_to_string(node: &Node) -> String {
+    scalar_to_string(&node.value).unwrap_or_else(|| {
+        // Verschachtelte Strukturen als JSON-String
+        serde_json::to_string(&JsonNode::new(node, &JsonOptions::default())).unwrap_or_default()
+    })
+}
+
+// ============================================================================
+// TYPISIERTES LESEN (serde)
+// ============================================================================
+
+/// Liest CSV direkt in einen eigenen Typ, z.B. `Vec<Zeile>`
+///
+/// Anders als beim Zwischenmodell bestimmt hier der Ziel-Typ, wie eine Zelle
+/// gelesen wird: eine Postleitzahl `"01234"` bleibt in einem `String`-Feld
+/// erhalten, eine leere Zelle wird in einem `Option`-Feld zu `None`. Zeilen
+/// mit Header sind Maps, ohne Header Sequenzen (z.B. für Tupel).
+///
+/// # Beispiel
+/// ```
+/// use asp_cli::formats::csv::{deserialize_csv, CsvOptions};
+///
+/// let rows: Vec<(String, Option<u32>)> =
+///     deserialize_csv("01234,5\n08150,\n", &CsvOptions { has_headers: false, ..CsvOptions::default() })?;
+/// assert_eq!(rows[1], ("08150".to_string(), None));
+/// # Ok::<(), asp_cli::FormatError>(())
+/// ```
+pub fn deserialize_csv<T: DeserializeOwned>(input: &str, options: &CsvOptions) -> Result<T, FormatError> {
+    let mut reader = options.reader_builder(input).from_reader(input.as_bytes());
+    let lines = LineIndex::new(input);
+
+    let headers = if options.has_headers {
+        let headers = reader.headers()
+            .map_err(|e| csv_parse_error(input, &lines, "Fehler beim Lesen der CSV-Header", e))?;
+        Some(headers.clone())
+    } else {
+        None
+    };
+    let records = reader
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| csv_parse_error(input, &lines, "Fehler beim Lesen eines CSV-Records", e))?;
+
+    let mut rows = CsvRows { headers: headers.as_ref(), records: records.iter(), current: None };
+    T::deserialize(&mut rows).map_err(|e| {
+        // Position der Zeile, bei der der Typ nicht gepasst hat
+        let span = rows.current.and_then(csv::StringRecord::position).map(|pos| lines.span(pos.byte() as usize, 0));
+        FormatError::parse("csv", format!("Zeile passt nicht zum erwarteten Typ: {}", e), input, span)
+    })
+}
+
+/// Alle Zeilen als Sequenz (Deserializer für das `Vec<Zeile>`)
+struct CsvRows<'a> {
+    headers: Option<&'a csv::StringRecord>,
+    records: std::slice::Iter<'a, csv::StringRecord>,
+    /// Zuletzt gelesene Zeile, für die Position im Fehler
+    current: Option<&'a csv::StringRecord>,
+}
+
+impl<'de> de::Deserializer<'de> for &mut CsvRows<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> SeqAccess<'de> for CsvRows<'_> {
+    type Error = de::value::Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<Option<S::Value>, Self::Error> {
+        let Some(record) = self.records.next() else {
+            return Ok(None);
+        };
+        self.current = Some(record);
+        match self.headers {
+            Some(headers) => seed
+                .deserialize(de::value::MapDeserializer::new(headers.iter().map(CsvCell::key).zip(record.iter().map(CsvCell::value))))
+                .map(Some),
+            None => seed.deserialize(de::value::SeqDeserializer::new(record.iter().map(CsvCell::value))).map(Some),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.records.len())
+    }
+}
+
+/// Eine Zelle (bzw. ein Header), gelesen als der Typ, den das Ziel verlangt
+#[derive(Clone, Copy)]
+struct CsvCell<'a> {
+    text: &'a str,
+    /// Header bleiben immer Strings, Zellen ohne Ziel-Typ werden wie beim
+    /// Zwischenmodell erkannt (`infer_type`)
+    is_key: bool,
+}
+
+impl<'a> CsvCell<'a> {
+    fn key(text: &'a str) -> Self {
+        CsvCell { text, is_key: true }
+    }
+
+    fn value(text: &'a str) -> Self {
+        CsvCell { text, is_key: false }
+    }
+
+    fn parse<T: std::str::FromStr>(&self, expected: &dyn de::Expected) -> Result<T, de::value::Error> {
+        self.text.trim().parse().map_err(|_| de::Error::invalid_value(Unexpected::Str(self.text), expected))
+    }
+}
+
+impl<'de> de::IntoDeserializer<'de, de::value::Error> for CsvCell<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Zahl aus der Zelle parsen und an den Visitor geben
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                let value = self.parse(&visitor)?;
+                visitor.$visit(value)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for CsvCell<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.is_key {
+            return visitor.visit_str(self.text);
+        }
+        match infer_type(self.text) {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Integer(i) => visitor.visit_i64(i),
+            Value::Float(f) => visitor.visit_f64(f),
+            _ => visitor.visit_str(self.text),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.text.trim().to_lowercase().as_str() {
+            "true" => visitor.visit_bool(true),
+            "false" => visitor.visit_bool(false),
+            _ => Err(de::Error::invalid_value(Unexpected::Str(self.text), &visitor)),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.text)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.text)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.text)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.text)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.text.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Enums ohne Daten über den Namen der Variante
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(de::value::StrDeserializer::new(self.text))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct ignored_any
+    }
+}
+
 // ============================================================================
 // FILE-I/O WRAPPER FUNKTIONEN (nur für CLI)
 // ============================================================================
 
 /// Konvertiert CSV zu JSON (File-I/O Wrapper)
 pub fn convert_csv_to_json(
-    input_path: &str,
-    output_path: &str
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = csv_to_json_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
 
 /// Konvertiert CSV zu YAML (File-I/O Wrapper)
 pub fn convert_csv_to_yaml(
-    input_path: &str,
-    output_path: &str
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = csv_to_yaml_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
 
 /// Konvertiert CSV zu TOML (File-I/O Wrapper)
 pub fn convert_csv_to_toml(
-    input_path: &str,
-    output_path: &str
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = csv_to_toml_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
 
 /// Konvertiert CSV zu CSV (File-I/O Wrapper)
 pub fn convert_csv_to_csv(
-    input_path: &str,
-    output_path: &str
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = csv_to_csv_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
@@ -198,134 +1401,233 @@ pub fn convert_csv_to_csv(
 /// 2. Integer
 /// 3. Float
 /// 4. String (fallback)
-fn infer_type(value: &str) -> JsonValue {
+fn infer_type(value: &str) -> Value {
     // Leerer String → null
     if value.is_empty() {
-        return JsonValue::Null;
+        return Value::Null;
     }
     
     // Boolean
     match value.to_lowercase().as_str() {
-        "true" => return JsonValue::Bool(true),
-        "false" => return JsonValue::Bool(false),
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
         _ => {}
     }
     
-    // Integer
-    if let Ok(num) = value.parse::<i64>() {
-        return JsonValue::Number(num.into());
-    }
-    
-    // Float
-    if let Ok(num) = value.parse::<f64>() {
-        if let Some(json_num) = serde_json::Number::from_f64(num) {
-            return JsonValue::Number(json_num);
-        }
+    // Zahl ohne Genauigkeitsverlust: lange IDs werden BigInt, Preise mit
+    // vielen Stellen Decimal (NaN/Infinity bleiben Strings)
+    if let Some(number) = parse_number(value) {
+        return number;
     }
     
     // Fallback: String
-    JsonValue::String(value.to_string())
+    Value::String(value.to_string())
 }
 
 /// Konvertiert CSV mit Dot-Notation zu JSON (verschachtelt)
 /// Beispiel: "contact.email" wird zu {"contact": {"email": "..."}}
 pub fn convert_csv_to_json_nested(
-    input_path: &str,
-    output_path: &str
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>
 ) -> Result<(), FormatError> {
-    // 1. CSV-Datei lesen und parsen (mit Dot-Notation Support)
-    let records = read_csv_to_json_nested(input_path)?;
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
+    // 1. CSV-Datei lesen
+    let content = fs::read_to_string(input_path)
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von CSV {}: {}", input_path.display(), e)))?;
     
-    // 2. Vec → JSON Array
-    let json_value = JsonValue::Array(records);
+    // 2. CSV parsen (mit Dot-Notation Support) → Array von Objekten
+    let records = parse_csv_records(&content, true, &CsvOptions::default())?;
     
-    // 3. JSON Value → Pretty-Printed String
-    let json_string = serde_json::to_string_pretty(&json_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Serialisieren von JSON: {}", e)))?;
+    // 3. Dokument → Pretty-Printed JSON
+    let json_string = write_json(&Document::new(Node::array(records)))?;
     
     // 4. String in Datei schreiben
     fs::write(output_path, json_string)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
 
     Ok(())
 }
 
-// Hinweis: read_csv_to_json_flat wurde durch parse_csv_to_json_values ersetzt (siehe oben)
-
-/// Hilfsfunktion: Liest CSV mit Dot-Notation und erstellt verschachtelte Strukturen
-/// 
-/// Diese Funktion wird für die separate `convert_csv_to_json_nested()` Funktion verwendet.
-/// Beispiel:
-/// CSV: name,contact.email,contact.phone
-/// JSON: {"name": "...", "contact": {"email": "...", "phone": "..."}}
-fn read_csv_to_json_nested(input_path: &str) -> Result<Vec<JsonValue>, FormatError> {
-    // 1. CSV-Reader erstellen
-    let mut reader = ReaderBuilder::new()
-        .has_headers(true)
-        .from_path(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von CSV {}: {}", input_path, e)))?;
-    
-    // 2. Header lesen
-    let headers = reader.headers()
-        .map_err(|e| FormatError::ParseError(format!("Fehler beim Lesen der CSV-Header: {}", e)))?
-        .clone();
-    
-    let header_vec: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
-    
-    // 3. Records lesen und in verschachtelte JSON-Objekte konvertieren
-    let mut records = Vec::new();
-    
-    for result in reader.records() {
-        let record = result
-            .map_err(|e| FormatError::ParseError(format!("Fehler beim Lesen eines CSV-Records: {}", e)))?;
-        
-        // Jedes Record wird ein JSON-Objekt (möglicherweise verschachtelt)
-        let mut root = serde_json::Map::new();
-        
-        for (i, field) in record.iter().enumerate() {
-            if let Some(header) = header_vec.get(i) {
-                // Typ-Inferenz wie gewohnt
-                let value = infer_type(field);
-                
-                // Verschachtelung durch Dot-Notation erstellen
-                insert_nested_value(&mut root, header, value);
-            }
-        }
-        
-        records.push(JsonValue::Object(root));
-    }
-    
-    Ok(records)
-}
-
 /// Hilfsfunktion: Fügt einen Wert in ein verschachteltes Objekt ein
 /// 
 /// Beispiel: insert_nested_value(obj, "contact.email", "test@test.com")
 /// Erstellt: {"contact": {"email": "test@test.com"}}
 fn insert_nested_value(
-    obj: &mut serde_json::Map<String, JsonValue>,
+    obj: &mut Map,
     key: &str,
-    value: JsonValue
+    value: Node
 ) {
-    let parts: Vec<&str> = key.split('.').collect();
-    
-    if parts.len() == 1 {
+    match key.split_once('.') {
         // Einfacher Key ohne Verschachtelung
-        obj.insert(key.to_string(), value);
-    } else {
+        None => {
+            obj.insert(Node::string(key), value);
+        }
         // Verschachtelter Key: z.B. "contact.email"
-        let first = parts[0];
-        let rest = parts[1..].join(".");
-        
-        // Hole oder erstelle das verschachtelte Objekt
-        let nested = obj.entry(first.to_string())
-            .or_insert_with(|| JsonValue::Object(serde_json::Map::new()));
-        
-        // Stelle sicher, dass es ein Objekt ist
-        if let JsonValue::Object(nested_map) = nested {
-            // Rekursiv für tiefere Verschachtelung
-            insert_nested_value(nested_map, &rest, value);
+        Some((first, rest)) => {
+            // Hole oder erstelle das verschachtelte Objekt
+            if obj.get(first).and_then(Node::as_map).is_none() {
+                obj.insert(Node::string(first), Node::map(Map::new()));
+            }
+            
+            if let Some(Node { value: Value::Map(nested_map), .. }) = obj.get_mut(first) {
+                // Rekursiv für tiefere Verschachtelung
+                insert_nested_value(nested_map, rest, value);
+            }
         }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excel_dialect_is_sniffed() {
+        let doc = read_csv("name;preis\nKaffee;\"3,50\"\nTee;2\n").unwrap();
+        let rows = doc.root.as_array().unwrap();
+        assert_eq!(rows[0].as_map().unwrap().get("preis").and_then(Node::as_str), Some("3,50"));
+        assert_eq!(rows[1].as_map().unwrap().get("preis").map(|n| &n.value), Some(&Value::Integer(2)));
+    }
+
+    #[test]
+    fn test_streaming_sniffs_whole_first_line() {
+        // Header länger als der Puffer von BufReader, Kommas nur in Anführungszeichen
+        let header: Vec<String> = (0..2000).map(|i| format!("'spalte,{}'", i)).collect();
+        let input = format!("{}\n{}\n", header.join(";"), vec!["1"; 2000].join(";"));
+        let options = CsvOptions { quote: b'\'', ..CsvOptions::default() };
+
+        let mut reader = std::io::BufReader::new(input.as_bytes());
+        let records: Vec<Node> = CsvRecords::new(&mut reader, &options).collect::<Result<_, _>>().unwrap();
+        let row = records[0].as_map().unwrap();
+        assert_eq!(row.len(), 2000);
+        assert_eq!(row.get("spalte,1999").map(|n| &n.value), Some(&Value::Integer(1)));
+    }
+
+    #[test]
+    fn test_headers_keep_source_order() {
+        let doc = crate::formats::json::read_json(r#"[{"z": 1, "a": 2}, {"m": 3, "z": 4}]"#).unwrap();
+        assert_eq!(write_csv(&doc).unwrap(), "z,a,m\n1,2,\n4,,3\n");
+
+        let sorted = ConvertOptions { sort_keys: true, ..ConvertOptions::default() };
+        assert_eq!(CsvFormat.write_with(&doc, &sorted).unwrap(), "a,m,z\n2,,1\n,3,4\n");
+    }
+
+    #[test]
+    fn test_tsv_forces_tab() {
+        let options = ConvertOptions {
+            csv: CsvOptions { delimiter: Some(b';'), ..CsvOptions::default() },
+            ..ConvertOptions::default()
+        };
+        let doc = TSV.read_with("a\tb\n1\t2\n", &options).unwrap();
+        assert_eq!(TSV.write_with(&doc, &options).unwrap(), "a\tb\n1\t2\n");
+        assert_eq!(PSV.write(&doc).unwrap(), "a|b\n1|2\n");
+    }
+
+    #[test]
+    fn test_headerless_roundtrip() {
+        let options = CsvOptions { delimiter: Some(b'|'), has_headers: false, ..CsvOptions::default() };
+        let input = "1|a\n2|b\n";
+        let doc = read_csv_with(input, &options).unwrap();
+        assert!(doc.root.as_array().unwrap()[0].as_array().is_some());
+        assert_eq!(write_csv_with(&doc, &options).unwrap(), input);
+    }
+
+    #[test]
+    fn test_streaming_header_union_and_columns() {
+        use crate::format::FileFormat;
+        use crate::stream::convert_stream_two_pass;
+        use std::io::Cursor;
+
+        let input = r#"[{"id": 1, "tags": ["a, b"]}, {"id": 2, "user": {"name": "x"}}]"#;
+        let mut output = Vec::new();
+        let mut reader = Cursor::new(input.as_bytes());
+        let options = ConvertOptions::default();
+        convert_stream_two_pass(&FileFormat::Json, &FileFormat::Csv, &mut reader, &mut output, &options).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), FileFormat::Json.convert(input, FileFormat::Csv).unwrap());
+
+        let options = ConvertOptions {
+            csv: CsvOptions { columns: Some(vec!["user_name".to_string(), "id".to_string()]), ..CsvOptions::default() },
+            ..ConvertOptions::default()
+        };
+        let mut output = Vec::new();
+        let mut reader = Cursor::new(input.as_bytes());
+        let warnings =
+            convert_stream_two_pass(&FileFormat::Json, &FileFormat::Csv, &mut reader, &mut output, &options).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "user_name,id\n,1\nx,2\n");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_streamed_records_match_and_report_lines() {
+        let options = ConvertOptions::default();
+        let input = "name;preis\nKaffee;\"3,50\"\nTee;2\n";
+        let mut bytes = input.as_bytes();
+        let records = CsvFormat.read_records(&mut bytes, &options).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(Document::new(Node::array(records)), read_csv(input).unwrap());
+
+        let mut bytes = "a,b\n1,2\n3\n".as_bytes();
+        let err = CsvFormat.read_records(&mut bytes, &options).unwrap().find_map(Result::err).unwrap();
+        assert_eq!(err.span().map(|s| s.line), Some(3));
+    }
+
+    #[test]
+    fn test_flatten_strategies() {
+        let doc = crate::formats::json::read_json(
+            r#"[{"id": 1, "user": {"first_name": "A"}, "tags": ["x", "y"], "items": [{"n": 1}, {"n": 2}]}]"#,
+        )
+        .unwrap();
+        let write = |separator: &str, arrays: ArrayMode, max_depth: Option<usize>| {
+            let options = ConvertOptions {
+                csv: CsvOptions {
+                    flatten: FlattenOptions { separator: separator.to_string(), arrays, max_depth },
+                    ..CsvOptions::default()
+                },
+                ..ConvertOptions::default()
+            };
+            let mut warnings = Vec::new();
+            let csv = CsvFormat.write_with_warnings(&doc, &options, &mut warnings).unwrap();
+            (csv, warnings.len())
+        };
+
+        assert_eq!(
+            write("/", ArrayMode::Index, None),
+            ("id,user/first_name,tags/0,tags/1,items/0/n,items/1/n\n1,A,x,y,1,2\n".to_string(), 0)
+        );
+        assert_eq!(
+            write(".", ArrayMode::Join("|".to_string()), None),
+            ("id,user.first_name,tags,items\n1,A,x|y,\"{\"\"n\"\":1}|{\"\"n\"\":2}\"\n".to_string(), 2)
+        );
+        assert_eq!(
+            write("_", ArrayMode::Explode, None).0,
+            "id,user_first_name,tags,items_n\n1,A,x,1\n1,A,x,2\n1,A,y,1\n1,A,y,2\n"
+        );
+        assert_eq!(
+            write("_", ArrayMode::Index, Some(1)),
+            ("id,user,tags,items\n1,\"{\"\"first_name\"\":\"\"A\"\"}\",\"[\"\"x\"\",\"\"y\"\"]\",\"[{\"\"n\"\":1},{\"\"n\"\":2}]\"\n".to_string(), 3)
+        );
+        assert_eq!("join:, ".parse::<ArrayMode>().unwrap(), ArrayMode::Join(", ".to_string()));
+    }
+
+    #[test]
+    fn test_flatten_column_collision() {
+        use crate::formats::json::JsonFormat;
+        use crate::stream::convert_stream;
+        use crate::warning::{write_reporting, WarningKind};
+
+        let input = r#"[{"a_b": 1, "a": {"b": 2}}]"#;
+        let doc = crate::formats::json::read_json(input).unwrap();
+        let mut warnings = Vec::new();
+        let csv = CsvFormat.write_with_warnings(&doc, &ConvertOptions::default(), &mut warnings).unwrap();
+        assert_eq!(csv, "a_b\n2\n");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!((warnings[0].kind, warnings[0].path.as_str()), (WarningKind::ColumnCollision, "/0"));
+
+        let mut output = Vec::new();
+        let streamed = convert_stream(&JsonFormat, &CsvFormat, &mut input.as_bytes(), &mut output, &ConvertOptions::default()).unwrap();
+        assert_eq!(streamed, warnings);
+
+        let strict = ConvertOptions { strict: true, ..ConvertOptions::default() };
+        assert!(write_reporting(&CsvFormat, &doc, &strict).is_err());
+        assert!(convert_stream(&JsonFormat, &CsvFormat, &mut input.as_bytes(), &mut Vec::new(), &strict).is_err());
+    }
+}
@@ -4,8 +4,544 @@
 
 // use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::Path;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+use crate::document::{parse_number, DateTime, Document, Map, Node, Span, Value};
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::value::RawValue;
 use crate::error::FormatError;
-use crate::formats::utils::json_to_toml_value;
+use crate::options::ConvertOptions;
+use crate::registry::{Capabilities, Format};
+use crate::stream::{write_error, RecordWriter, Records};
+use crate::formats::csv::write_csv;
+use crate::formats::toml::write_toml;
+use crate::formats::utils::{datetime_to_epoch, LineIndex};
+use crate::formats::yaml::write_yaml;
+
+// ============================================================================
+// FORMAT-DEFINITION (für die FormatRegistry)
+// ============================================================================
+
+/// JSON als registrierbares Format
+pub struct JsonFormat;
+
+impl Format for JsonFormat {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["json"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: true,
+            write: true,
+            nested: true,
+            root_array: true,
+            null: true,
+            datetime: false,
+            tags: false,
+            aliases: false,
+        }
+    }
+
+    fn read(&self, input: &str) -> Result<Document, FormatError> {
+        read_json(input)
+    }
+
+    fn read_with(&self, input: &str, options: &ConvertOptions) -> Result<Document, FormatError> {
+        read_json_with(input, &options.json)
+    }
+
+    fn write(&self, document: &Document) -> Result<String, FormatError> {
+        write_json(document)
+    }
+
+    fn write_with(&self, document: &Document, options: &ConvertOptions) -> Result<String, FormatError> {
+        write_json_with(document, &options.json)
+    }
+
+    /// Nur ein Array als Root wird gestreamt, alles andere als Ganzes gelesen
+    fn read_records<'a>(&self, input: &'a mut dyn BufRead, options: &ConvertOptions) -> Option<Records<'a>> {
+        match JsonArrayRecords::open(input, &options.json) {
+            Ok(records) => records.map(|records| Box::new(records) as Records<'a>),
+            // Ein Lesefehler ist kein Grund, das Dokument als Ganzes zu lesen
+            Err(error) => Some(Box::new(std::iter::once(Err(error)))),
+        }
+    }
+
+    fn record_writer<'a>(
+        &self,
+        output: &'a mut dyn Write,
+        options: &ConvertOptions,
+    ) -> Option<Box<dyn RecordWriter + 'a>> {
+        Some(Box::new(JsonArrayWriter { output, options: options.json.clone(), count: 0 }))
+    }
+}
+
+/// Key für Datetimes als Objekt: `{"$datetime": "1979-05-27T07:32:00Z"}`
+pub const DATETIME_KEY: &str = "$datetime";
+
+/// Einstellungen für JSON (Ausgabe und Rücklesen von Datetimes)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JsonOptions {
+    /// Darstellung von Datetimes, die JSON nicht kennt
+    pub datetime: JsonDateTime,
+}
+
+/// Wie Datetimes (z.B. aus TOML) in JSON geschrieben werden
+///
+/// Als Text (CLI, Web): `iso`, `epoch` oder `tagged`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum JsonDateTime {
+    /// String nach RFC 3339, z.B. "1979-05-27T07:32:00Z"
+    #[default]
+    Iso,
+    /// Sekunden seit 1970-01-01 UTC (mit Nachkommastellen, falls nötig).
+    /// Ohne Offset gilt UTC, reine Uhrzeiten bleiben Strings.
+    Epoch,
+    /// Objekt `{"$datetime": "..."}` - wird beim Lesen mit derselben Option
+    /// wieder zum Datetime, damit z.B. TOML → JSON → TOML verlustfrei bleibt
+    Tagged,
+}
+
+impl FromStr for JsonDateTime {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "iso" => Ok(JsonDateTime::Iso),
+            "epoch" => Ok(JsonDateTime::Epoch),
+            "tagged" => Ok(JsonDateTime::Tagged),
+            _ => Err(FormatError::InvalidFormat(format!(
+                "Unbekannte Datetime-Darstellung: {} (erlaubt: iso, epoch, tagged)",
+                s
+            ))),
+        }
+    }
+}
+
+// ============================================================================
+// READER / WRITER (Zwischenmodell)
+// ============================================================================
+
+/// Liest einen JSON String in das Zwischenmodell
+///
+/// Objekte wie `{"$datetime": "..."}` bleiben hier normale Objekte, siehe
+/// `read_json_with`.
+pub fn read_json(input: &str) -> Result<Document, FormatError> {
+    read_json_with(input, &JsonOptions::default())
+}
+
+/// Wie `read_json`, mit `JsonDateTime::Tagged` werden Objekte der Form
+/// `{"$datetime": "..."}` mit gültigem Datetime wieder zu Datetimes
+///
+/// # Beispiel
+/// ```
+/// use asp_cli::document::{Document, Value};
+/// use asp_cli::formats::json::{read_json, read_json_with, JsonDateTime, JsonOptions};
+///
+/// let input = r#"{"at": {"$datetime": "1979-05-27T07:32:00Z"}}"#;
+/// let options = JsonOptions { datetime: JsonDateTime::Tagged };
+/// let at = |doc: Document| doc.root.as_map().and_then(|map| map.get("at")).map(|at| at.value.clone());
+/// assert!(matches!(at(read_json_with(input, &options)?), Some(Value::DateTime(_))));
+/// assert!(matches!(at(read_json(input)?), Some(Value::Map(_))));
+/// # Ok::<(), asp_cli::FormatError>(())
+/// ```
+pub fn read_json_with(input: &str, options: &JsonOptions) -> Result<Document, FormatError> {
+    serde_json::from_str::<&RawValue>(input)
+        .and_then(|raw| raw_to_node(raw, options))
+        .map(Document::new)
+        .map_err(|e| json_parse_error(input, e))
+}
+
+/// serde_json-Fehler → ParseError mit Position und Ausschnitt
+fn json_parse_error(input: &str, error: serde_json::Error) -> FormatError {
+    // Die Position steht schon im Span, nicht nochmal in der Meldung
+    let message = error.to_string();
+    let suffix = format!(" at line {} column {}", error.line(), error.column());
+    let message = message.strip_suffix(&suffix).unwrap_or(&message);
+
+    let span = (error.line() > 0)
+        .then(|| LineIndex::new(input).span_at_byte_column(error.line(), error.column()));
+    FormatError::parse("json", message, input, span)
+}
+
+/// Schreibt das Zwischenmodell als formatierten JSON String
+pub fn write_json(doc: &Document) -> Result<String, FormatError> {
+    write_json_with(doc, &JsonOptions::default())
+}
+
+/// Wie `write_json`, mit wählbarer Darstellung für Datetimes
+///
+/// # Beispiel
+/// ```
+/// use asp_cli::formats::json::{write_json_with, JsonDateTime, JsonOptions};
+/// use asp_cli::formats::toml::read_toml;
+///
+/// let doc = read_toml("at = 1970-01-02T00:00:00Z")?;
+/// let options = JsonOptions { datetime: JsonDateTime::Epoch };
+/// assert_eq!(write_json_with(&doc, &options)?, "{\n  \"at\": 86400\n}");
+/// # Ok::<(), asp_cli::FormatError>(())
+/// ```
+pub fn write_json_with(doc: &Document, options: &JsonOptions) -> Result<String, FormatError> {
+    serde_json::to_string_pretty(&JsonNode::new(&doc.root, options))
+        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von JSON: {}", e)))
+}
+
+// ============================================================================
+// JSON ↔ ZWISCHENMODELL
+// ============================================================================
+//
+// serde_json rundet Zahlen ohne das Feature `arbitrary_precision` auf f64.
+// Das Feature ist aber keine private Entscheidung: Cargo schaltet es für
+// alle Crates im Build ein und ändert damit auch deren serde_json-Verhalten.
+// Deshalb wird jede Ebene als `RawValue` gelesen (Zahlen im Originaltext)
+// und beim Schreiben `BigInt`/`Decimal` als `RawValue` ausgegeben.
+
+/// JSON-Wert → Node, Zahlen behalten alle Stellen (siehe `parse_number`)
+///
+/// Doppelte Keys: der spätere Wert gewinnt (wie bei `Map::insert`).
+/// `{"$datetime": "..."}` wird nur mit `JsonDateTime::Tagged` zum Datetime.
+pub(crate) fn raw_to_node(raw: &RawValue, options: &JsonOptions) -> Result<Node, serde_json::Error> {
+    let text = raw.get();
+    let value = match text.as_bytes().first() {
+        Some(b'{') => {
+            let RawEntries(entries) = serde_json::from_str(text)?;
+            match tagged_datetime(&entries, options) {
+                Some(dt) => Value::DateTime(dt),
+                None => Value::Map(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| Ok((Node::string(key), raw_to_node(value, options)?)))
+                        .collect::<Result<Map, serde_json::Error>>()?,
+                ),
+            }
+        }
+        Some(b'[') => {
+            let items: Vec<&RawValue> = serde_json::from_str(text)?;
+            Value::Array(items.into_iter().map(|item| raw_to_node(item, options)).collect::<Result<_, _>>()?)
+        }
+        Some(b'"') => Value::String(serde_json::from_str(text)?),
+        _ => match text {
+            "null" => Value::Null,
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            number => parse_number(number).unwrap_or_else(|| Value::Decimal(number.to_string())),
+        },
+    };
+    Ok(Node::new(value))
+}
+
+/// Einträge eines Objekts in Quell-Reihenfolge, Werte noch ungelesen
+struct RawEntries<'a>(Vec<(String, &'a RawValue)>);
+
+impl<'de> Deserialize<'de> for RawEntries<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor;
+
+        impl<'de> Visitor<'de> for EntriesVisitor {
+            type Value = RawEntries<'de>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(RawEntries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(EntriesVisitor)
+    }
+}
+
+/// `{"$datetime": "..."}` → Datetime
+fn tagged_datetime(entries: &[(String, &RawValue)], options: &JsonOptions) -> Option<DateTime> {
+    if options.datetime != JsonDateTime::Tagged {
+        return None;
+    }
+    match entries {
+        [(key, value)] if key == DATETIME_KEY => serde_json::from_str::<String>(value.get()).ok()?.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Node für serde_json (Tags gehen verloren, Keys werden zu Strings)
+///
+/// `BigInt` und `Decimal` werden als `RawValue` unverändert geschrieben, das
+/// versteht nur der Serializer von serde_json.
+pub(crate) struct JsonNode<'a> {
+    node: &'a Node,
+    options: &'a JsonOptions,
+}
+
+impl<'a> JsonNode<'a> {
+    pub(crate) fn new(node: &'a Node, options: &'a JsonOptions) -> Self {
+        JsonNode { node, options }
+    }
+}
+
+impl Serialize for JsonNode<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let child = |node| JsonNode::new(node, self.options);
+        match &self.node.value {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Integer(i) => serializer.serialize_i64(*i),
+            // Alle Stellen bleiben erhalten, Schreibweisen wie ".5" (YAML)
+            // sind in JSON aber keine Zahl
+            Value::BigInt(s) | Value::Decimal(s) => match RawValue::from_string(s.clone()) {
+                Ok(raw) if s.starts_with(|c: char| c.is_ascii_digit() || c == '-') => raw.serialize(serializer),
+                _ => serialize_float(s.parse().unwrap_or(f64::NAN), serializer),
+            },
+            Value::Float(f) => serialize_float(*f, serializer),
+            Value::String(s) => serializer.serialize_str(s),
+            Value::DateTime(dt) => match self.options.datetime {
+                JsonDateTime::Epoch => match datetime_to_epoch(dt) {
+                    Some((seconds, 0)) => serializer.serialize_i64(seconds),
+                    Some((seconds, nanos)) => serialize_float(seconds as f64 + f64::from(nanos) / 1e9, serializer),
+                    None => serializer.collect_str(dt),
+                },
+                JsonDateTime::Tagged => serializer.collect_map([(DATETIME_KEY, dt.to_string())]),
+                JsonDateTime::Iso => serializer.collect_str(dt),
+            },
+            Value::Bytes(_) => {
+                serializer.serialize_str(&crate::document::scalar_to_string(&self.node.value).unwrap_or_default())
+            }
+            Value::Array(items) => serializer.collect_seq(items.iter().map(child)),
+            Value::Map(map) => serializer.collect_map(map.iter().map(|(k, v)| (k.to_key_string(), child(v)))),
+        }
+    }
+}
+
+/// NaN und Infinity gibt es in JSON nicht - sie werden zu null
+fn serialize_float<S: Serializer>(f: f64, serializer: S) -> Result<S::Ok, S::Error> {
+    if f.is_finite() { serializer.serialize_f64(f) } else { serializer.serialize_unit() }
+}
+
+// ============================================================================
+// STREAMING
+// ============================================================================
+
+/// Liest die Einträge eines Root-Arrays einzeln (siehe `Format::read_records`)
+///
+/// Ein Eintrag wird bis zum passenden `,` bzw. `]` gesammelt und dann mit
+/// serde_json geparst, im Speicher liegt also immer nur ein Eintrag.
+struct JsonArrayRecords<'a> {
+    input: &'a mut dyn BufRead,
+    element: Vec<u8>,
+    /// Position hinter dem zuletzt gelesenen Byte (Spalte in Bytes, 1-basiert)
+    line: usize,
+    column: usize,
+    offset: usize,
+    /// Vor dem ersten Eintrag (kein `,` erwartet)
+    first: bool,
+    done: bool,
+    options: JsonOptions,
+}
+
+impl<'a> JsonArrayRecords<'a> {
+    /// `Ok(None)`, wenn die Eingabe nicht mit `[` beginnt (nur Leerraum wird gelesen)
+    fn open(input: &'a mut dyn BufRead, options: &JsonOptions) -> Result<Option<Self>, FormatError> {
+        let mut records = JsonArrayRecords {
+            input,
+            element: Vec::new(),
+            line: 1,
+            column: 1,
+            offset: 0,
+            first: true,
+            done: false,
+            options: options.clone(),
+        };
+        // BOM am Anfang gehört nicht zum JSON
+        if records.input.fill_buf().map_err(Self::io_error)?.starts_with("\u{feff}".as_bytes()) {
+            records.input.consume(3);
+            records.offset = 3;
+        }
+        if records.skip_whitespace()? != Some(b'[') {
+            return Ok(None);
+        }
+        records.advance(1);
+        Ok(Some(records))
+    }
+
+    fn io_error(error: std::io::Error) -> FormatError {
+        FormatError::IoError(format!("Fehler beim Lesen der Eingabe: {}", error))
+    }
+
+    /// Fehler an der aktuellen Position
+    fn error(&mut self, message: &str) -> FormatError {
+        self.done = true;
+        let span = Span { offset: self.offset, len: 1, line: self.line, column: self.column };
+        FormatError::ParseError { format: "json".to_string(), message: message.to_string(), span: Some(span), snippet: None }
+    }
+
+    /// Verbraucht `count` Bytes aus dem Puffer und führt die Position nach
+    fn advance(&mut self, count: usize) {
+        if let Ok(buffer) = self.input.fill_buf() {
+            for &byte in &buffer[..count.min(buffer.len())] {
+                self.offset += 1;
+                if byte == b'\n' {
+                    self.line += 1;
+                    self.column = 1;
+                } else {
+                    self.column += 1;
+                }
+            }
+        }
+        self.input.consume(count);
+    }
+
+    /// Überspringt Leerraum und gibt das nächste Byte zurück, ohne es zu lesen
+    fn skip_whitespace(&mut self) -> Result<Option<u8>, FormatError> {
+        loop {
+            let buffer = self.input.fill_buf().map_err(Self::io_error)?;
+            let Some(&first) = buffer.first() else {
+                return Ok(None);
+            };
+            let skip = buffer.iter().take_while(|b| b.is_ascii_whitespace()).count();
+            if skip == 0 {
+                return Ok(Some(first));
+            }
+            self.advance(skip);
+        }
+    }
+
+    /// Sammelt einen Eintrag bis vor das `,` bzw. `]` auf oberster Ebene
+    fn read_element(&mut self) -> Result<(), FormatError> {
+        self.element.clear();
+        let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+        loop {
+            let buffer = self.input.fill_buf().map_err(Self::io_error)?;
+            if buffer.is_empty() {
+                return Ok(());
+            }
+            let mut end = None;
+            for (i, &byte) in buffer.iter().enumerate() {
+                if in_string {
+                    match byte {
+                        _ if escaped => escaped = false,
+                        b'\\' => escaped = true,
+                        b'"' => in_string = false,
+                        _ => {}
+                    }
+                    continue;
+                }
+                match byte {
+                    b'"' => in_string = true,
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' if depth > 0 => depth -= 1,
+                    b',' | b']' if depth == 0 => {
+                        end = Some(i);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            let taken = end.unwrap_or(buffer.len());
+            self.element.extend_from_slice(&buffer[..taken]);
+            self.advance(taken);
+            if end.is_some() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// serde_json-Fehler im Eintrag → Position in der ganzen Eingabe
+    fn element_error(&mut self, start: (usize, usize, usize), error: serde_json::Error) -> FormatError {
+        self.done = true;
+        let message = error.to_string();
+        let suffix = format!(" at line {} column {}", error.line(), error.column());
+        let message = message.strip_suffix(&suffix).unwrap_or(&message).to_string();
+
+        let (line, column, offset) = start;
+        let span = match error.line() {
+            0 | 1 => Span { offset, len: 0, line, column: column + error.column().saturating_sub(1) },
+            n => Span { offset, len: 0, line: line + n - 1, column: error.column() },
+        };
+        FormatError::ParseError { format: "json".to_string(), message, span: Some(span), snippet: None }
+    }
+}
+
+impl Iterator for JsonArrayRecords<'_> {
+    type Item = Result<Node, FormatError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = match self.skip_whitespace() {
+            Ok(next) => next,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        };
+        match next {
+            Some(b']') => {
+                self.advance(1);
+                self.done = true;
+                // Hinter dem Array darf nur noch Leerraum stehen
+                return match self.skip_whitespace() {
+                    Ok(None) => None,
+                    Ok(Some(_)) => Some(Err(self.error("trailing characters"))),
+                    Err(e) => Some(Err(e)),
+                };
+            }
+            Some(b',') if !self.first => self.advance(1),
+            None => return Some(Err(self.error("EOF while parsing a list"))),
+            Some(_) if !self.first => return Some(Err(self.error("expected `,` or `]`"))),
+            Some(_) => {}
+        }
+        self.first = false;
+
+        if let Err(e) = self.skip_whitespace() {
+            self.done = true;
+            return Some(Err(e));
+        }
+        let start = (self.line, self.column, self.offset);
+        if let Err(e) = self.read_element() {
+            self.done = true;
+            return Some(Err(e));
+        }
+        let node = serde_json::from_slice::<&RawValue>(&self.element).and_then(|raw| raw_to_node(raw, &self.options));
+        Some(node.map_err(|e| self.element_error(start, e)))
+    }
+}
+
+/// Schreibt ein Root-Array Eintrag für Eintrag, Ausgabe wie `write_json_with`
+struct JsonArrayWriter<'a> {
+    output: &'a mut dyn Write,
+    options: JsonOptions,
+    count: usize,
+}
+
+impl RecordWriter for JsonArrayWriter<'_> {
+    fn write_record(&mut self, record: &Node) -> Result<(), FormatError> {
+        let text = serde_json::to_string_pretty(&JsonNode::new(record, &self.options))
+            .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von JSON: {}", e)))?;
+        let separator = if self.count == 0 { "[\n" } else { ",\n" };
+        self.count += 1;
+
+        // Eine Ebene tiefer eingerückt, wie im Array von to_string_pretty
+        let indented: Vec<String> = text.lines().map(|line| format!("  {}", line)).collect();
+        write!(self.output, "{}{}", separator, indented.join("\n")).map_err(write_error)
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), FormatError> {
+        let end = if self.count == 0 { "[]" } else { "\n]" };
+        self.output.write_all(end.as_bytes()).and_then(|_| self.output.flush()).map_err(write_error)
+    }
+}
 
 // ============================================================================
 // STRING-ZU-STRING FUNKTIONEN (Core-Logik für CLI und Web)
@@ -13,96 +549,22 @@ use crate::formats::utils::json_to_toml_value;
 
 /// Konvertiert JSON String zu formatiertem JSON String
 pub fn json_to_json_string(input: &str) -> Result<String, FormatError> {
-    let json_value: serde_json::Value = serde_json::from_str(input)
-        .map_err(|e| FormatError::ParseError(format!("Ungültiges JSON: {}", e)))?;
-    
-    serde_json::to_string_pretty(&json_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von JSON: {}", e)))
+    write_json(&read_json(input)?)
 }
 
 /// Konvertiert JSON String zu TOML String
 pub fn json_to_toml_string(input: &str) -> Result<String, FormatError> {
-    let json_value: serde_json::Value = serde_json::from_str(input)
-        .map_err(|e| FormatError::ParseError(format!("Ungültiges JSON: {}", e)))?;
-    
-    // TOML unterstützt kein Array als Root-Element
-    // Wenn es ein Array ist, wrappen wir es in ein Objekt
-    let toml_ready_value = match json_value {
-        serde_json::Value::Array(arr) => {
-            serde_json::json!({ "data": arr })
-        }
-        _ => json_value
-    };
-    
-    // JSON Value → TOML Value strukturell konvertieren
-    let toml_value = json_to_toml_value(&toml_ready_value)?;
-    
-    // TOML Value → Pretty-Printed String
-    toml::to_string_pretty(&toml_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Serialisieren von TOML: {}", e)))
+    write_toml(&read_json(input)?)
 }
 
 /// Konvertiert JSON String zu YAML String
 pub fn json_to_yaml_string(input: &str) -> Result<String, FormatError> {
-    let json_value: serde_json::Value = serde_json::from_str(input)
-        .map_err(|e| FormatError::ParseError(format!("Ungültiges JSON: {}", e)))?;
-    
-    serde_yaml::to_string(&json_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von YAML: {}", e)))
+    write_yaml(&read_json(input)?)
 }
 
 /// Konvertiert JSON String zu CSV String
 pub fn json_to_csv_string(input: &str) -> Result<String, FormatError> {
-    let json_value: serde_json::Value = serde_json::from_str(input)
-        .map_err(|e| FormatError::ParseError(format!("Ungültiges JSON: {}", e)))?;
-    
-    // JSON Array zu CSV
-    let array = match json_value {
-        serde_json::Value::Array(arr) => arr,
-        serde_json::Value::Object(_) => vec![json_value],
-        _ => return Err(FormatError::SerializationError("JSON muss ein Array oder Objekt sein für CSV".to_string()))
-    };
-    
-    if array.is_empty() {
-        return Ok(String::new());
-    }
-    
-    // Alle Objekte flattenen
-    let flattened: Vec<_> = array.iter()
-        .map(|v| flatten_json(v, ""))
-        .collect();
-    
-    // Header sammeln
-    let mut all_headers = std::collections::BTreeSet::new();
-    for obj in &flattened {
-        for key in obj.keys() {
-            all_headers.insert(key.clone());
-        }
-    }
-    let headers: Vec<String> = all_headers.into_iter().collect();
-    
-    // CSV Writer in Memory
-    let mut writer = csv::Writer::from_writer(vec![]);
-    
-    // Header schreiben
-    writer.write_record(&headers)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Schreiben der CSV-Header: {}", e)))?;
-    
-    // Daten schreiben
-    for flat_obj in flattened {
-        let row: Vec<String> = headers.iter()
-            .map(|h| flat_obj.get(h).cloned().unwrap_or_default())
-            .collect();
-        writer.write_record(&row)
-            .map_err(|e| FormatError::SerializationError(format!("Fehler beim Schreiben der CSV-Zeile: {}", e)))?;
-    }
-    
-    // Writer in String umwandeln
-    let data = writer.into_inner()
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Abschliessen von CSV: {}", e)))?;
-    
-    String::from_utf8(data)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler bei UTF-8 Konvertierung: {}", e)))
+    write_csv(&read_json(input)?)
 }
 
 // ============================================================================
@@ -111,48 +573,51 @@ pub fn json_to_csv_string(input: &str) -> Result<String, FormatError> {
 
 /// Konvertiert JSON zu formatiertem JSON (File-I/O Wrapper)
 pub fn convert_json_to_json(
-    input_path: &str,
-    output_path: &str,
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = json_to_json_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
 
 /// Konvertiert JSON zu TOML (File-I/O Wrapper)
 pub fn convert_json_to_toml(
-    input_path: &str,
-    output_path: &str,
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = json_to_toml_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
 
 /// Konvertiert JSON zu formatiertem YAML (File-I/O Wrapper)
 pub fn convert_json_to_yaml(
-    input_path: &str,
-    output_path: &str,
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = json_to_yaml_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
@@ -160,16 +625,17 @@ pub fn convert_json_to_yaml(
 
 /// Konvertiert JSON zu CSV (File-I/O Wrapper)
 pub fn convert_json_to_csv(
-    input_path: &str,
-    output_path: &str,
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = json_to_csv_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
@@ -177,71 +643,15 @@ pub fn convert_json_to_csv(
 
 
 /// Validiert eine JSON-Datei ohne sie zu schreiben
-pub fn validate_json(input_path: &str) -> Result<serde_json::Value, FormatError> {
+pub fn validate_json(input_path: impl AsRef<Path>) -> Result<serde_json::Value, FormatError> {
+    let input_path = input_path.as_ref();
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
-
-        // serde_json::from_str prüft ob syntax korrekt ist
-        // prüft ob syntax korrekt ist
-    let json_value: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| FormatError::ParseError(format!("Fehler beim Parsen von JSON: {}", e)))?;
-
-    Ok(json_value)
-}
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
 
+    // gleicher Reader wie bei der Konvertierung - was hier gültig ist,
+    // lässt sich auch konvertieren
+    let doc = read_json(&content)?;
 
-// __________________________________________________________________________________________________________________________
-// Helper-Funktionen für CSV-Konvertierung
-
-/// Konvertiert einen JSON-Wert zu einem String
-fn value_to_string(value: &serde_json::Value) -> String {
-    match value {
-        serde_json::Value::String(s) => s.clone(),
-        serde_json::Value::Number(n) => n.to_string(),
-        serde_json::Value::Bool(b) => b.to_string(),
-        serde_json::Value::Null => String::new(),
-        // Verschachtelte Strukturen als JSON-String
-        serde_json::Value::Object(_) | serde_json::Value::Array(_) => {
-            serde_json::to_string(value).unwrap_or_else(|_| String::new())
-        }
-    }
+    serde_json::from_str(&write_json(&doc)?)
+        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von JSON: {}", e)))
 }
-
-/// Flattened ein JSON-Objekt zu einer flachen Map mit Unterstrich-Trennzeichen
-/// 
-/// Beispiel:
-/// Input:  {"contact": {"email": "test@test.com", "phone": "+49"}}
-/// Output: {"contact_email": "test@test.com", "contact_phone": "+49"}
-fn flatten_json(value: &serde_json::Value, prefix: &str) -> std::collections::HashMap<String, String> {
-    use std::collections::HashMap;
-    
-    let mut result = HashMap::new();
-    
-    match value {
-        serde_json::Value::Object(obj) => {
-            for (key, val) in obj {
-                let new_key = if prefix.is_empty() {
-                    key.clone()
-                } else {
-                    format!("{}_{}", prefix, key)
-                };
-                
-                // Rekursiv für verschachtelte Objekte
-                if val.is_object() {
-                    let nested = flatten_json(val, &new_key);
-                    result.extend(nested);
-                } else {
-                    result.insert(new_key, value_to_string(val));
-                }
-            }
-        }
-        _ => {
-            // Primitive Werte direkt einfügen
-            if !prefix.is_empty() {
-                result.insert(prefix.to_string(), value_to_string(value));
-            }
-        }
-    }
-    
-    result
-}
\ No newline at end of file
@@ -1,6 +1,9 @@
 // Format Module exportieren
 pub mod json;
+pub mod ndjson;
 pub mod yaml;
 pub mod toml;
+pub mod toml_editor;
 pub mod csv;
+pub mod xml;
 pub mod utils;
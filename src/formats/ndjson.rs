@@ -0,0 +1,278 @@
+// NDJSON / JSON Lines: ein JSON-Wert pro Zeile, z.B. für Logs
+
+use std::io::{BufRead, Write};
+
+use serde_json::value::RawValue;
+
+use crate::document::{Document, Node, Value};
+use crate::error::FormatError;
+use crate::formats::json::{raw_to_node, JsonNode, JsonOptions};
+use crate::formats::utils::LineIndex;
+use crate::options::ConvertOptions;
+use crate::registry::{Capabilities, Format};
+use crate::stream::{write_error, RecordWriter, Records};
+
+// ============================================================================
+// FORMAT-DEFINITION (für die FormatRegistry)
+// ============================================================================
+
+/// NDJSON als registrierbares Format
+///
+/// Jede nicht-leere Zeile ist ein Datensatz, im Zwischenmodell ein Eintrag
+/// des Root-Arrays. Über `read_records` und `record_writer` lässt sich
+/// NDJSON Zeile für Zeile verarbeiten.
+pub struct NdjsonFormat;
+
+impl Format for NdjsonFormat {
+    fn name(&self) -> &str {
+        "ndjson"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["jsonl", "jsonlines"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ndjson", "jsonl"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: true,
+            write: true,
+            nested: true,
+            root_array: true,
+            null: true,
+            datetime: false,
+            tags: false,
+            aliases: false,
+        }
+    }
+
+    fn read(&self, input: &str) -> Result<Document, FormatError> {
+        read_ndjson(input)
+    }
+
+    fn read_with(&self, input: &str, options: &ConvertOptions) -> Result<Document, FormatError> {
+        read_ndjson_with(input, &options.json)
+    }
+
+    fn write(&self, document: &Document) -> Result<String, FormatError> {
+        write_ndjson(document)
+    }
+
+    fn write_with(&self, document: &Document, options: &ConvertOptions) -> Result<String, FormatError> {
+        write_ndjson_with(document, &options.json)
+    }
+
+    fn read_records<'a>(&self, input: &'a mut dyn BufRead, options: &ConvertOptions) -> Option<Records<'a>> {
+        Some(Box::new(NdjsonRecords::new(input, &options.json)))
+    }
+
+    fn record_writer<'a>(
+        &self,
+        output: &'a mut dyn Write,
+        options: &ConvertOptions,
+    ) -> Option<Box<dyn RecordWriter + 'a>> {
+        Some(Box::new(NdjsonWriter { output, options: options.json.clone() }))
+    }
+}
+
+// ============================================================================
+// READER / WRITER (Zwischenmodell)
+// ============================================================================
+
+/// Liest NDJSON in das Zwischenmodell (Array mit einem Eintrag pro Zeile)
+///
+/// Leere Zeilen werden übersprungen. Fehler enthalten die Zeile in der
+/// ganzen Eingabe.
+///
+/// # Beispiel
+/// ```
+/// use asp_cli::formats::ndjson::read_ndjson;
+///
+/// let doc = read_ndjson("{\"a\": 1}\n\n{\"a\": 2}\n")?;
+/// assert_eq!(doc.root.as_array().map(Vec::len), Some(2));
+///
+/// let err = read_ndjson("{\"a\": 1}\n{\"a\": }\n").unwrap_err();
+/// assert_eq!(err.span().map(|s| s.line), Some(2));
+/// # Ok::<(), asp_cli::FormatError>(())
+/// ```
+pub fn read_ndjson(input: &str) -> Result<Document, FormatError> {
+    read_ndjson_with(input, &JsonOptions::default())
+}
+
+/// Wie `read_ndjson`, `{"$datetime": "..."}` wird wie bei `read_json_with` gelesen
+pub fn read_ndjson_with(input: &str, options: &JsonOptions) -> Result<Document, FormatError> {
+    let mut bytes = input.as_bytes();
+    let records = NdjsonRecords::new(&mut bytes, options).collect::<Result<Vec<_>, _>>()?;
+    Ok(Document::new(Node::array(records)))
+}
+
+/// Schreibt das Zwischenmodell als NDJSON (ein Eintrag des Root-Arrays pro Zeile)
+pub fn write_ndjson(doc: &Document) -> Result<String, FormatError> {
+    write_ndjson_with(doc, &JsonOptions::default())
+}
+
+/// Wie `write_ndjson`, mit wählbarer Darstellung für Datetimes
+///
+/// Eine Root, die kein Array ist, wird eine einzelne Zeile.
+pub fn write_ndjson_with(doc: &Document, options: &JsonOptions) -> Result<String, FormatError> {
+    let records = match &doc.root.value {
+        Value::Array(items) => items.iter().collect(),
+        _ => vec![&doc.root],
+    };
+
+    let mut output = String::new();
+    for record in records {
+        output.push_str(&record_to_line(record, options)?);
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+/// Ein Datensatz als kompakte JSON-Zeile (ohne Zeilenumbruch)
+fn record_to_line(record: &Node, options: &JsonOptions) -> Result<String, FormatError> {
+    serde_json::to_string(&JsonNode::new(record, options))
+        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von NDJSON: {}", e)))
+}
+
+// ============================================================================
+// STREAMING
+// ============================================================================
+
+/// Liest NDJSON Zeile für Zeile, nur die aktuelle Zeile liegt im Speicher
+struct NdjsonRecords<'a> {
+    input: &'a mut dyn BufRead,
+    line: String,
+    /// Nummer der zuletzt gelesenen Zeile (1-basiert)
+    line_number: usize,
+    /// Byte-Offset der nächsten Zeile in der ganzen Eingabe
+    offset: usize,
+    /// Nach einem Lesefehler ist der Stream zu Ende
+    done: bool,
+    options: JsonOptions,
+}
+
+impl<'a> NdjsonRecords<'a> {
+    fn new(input: &'a mut dyn BufRead, options: &JsonOptions) -> Self {
+        NdjsonRecords { input, line: String::new(), line_number: 0, offset: 0, done: false, options: options.clone() }
+    }
+
+    /// serde_json-Fehler → ParseError mit Zeile in der ganzen Eingabe
+    ///
+    /// `text` ist der geparste Teil der Zeile (ohne BOM), dazu passt die
+    /// Spalte von serde_json.
+    fn parse_error(&self, text: &str, line_start: usize, error: serde_json::Error) -> FormatError {
+        let message = error.to_string();
+        let suffix = format!(" at line {} column {}", error.line(), error.column());
+        let message = message.strip_suffix(&suffix).unwrap_or(&message);
+
+        let mut span = LineIndex::new(text).span_at_byte_column(1, error.column().max(1));
+        span.offset += line_start + (self.line.len() - text.len());
+        span.line = self.line_number;
+        FormatError::parse_line("ndjson", message, text, span)
+    }
+}
+
+impl Iterator for NdjsonRecords<'_> {
+    type Item = Result<Node, FormatError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.line.clear();
+            let read = match self.input.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(read) => read,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(FormatError::IoError(format!(
+                        "Fehler beim Lesen von Zeile {}: {}",
+                        self.line_number + 1,
+                        e
+                    ))));
+                }
+            };
+            let line_start = self.offset;
+            self.offset += read;
+            self.line_number += 1;
+
+            // BOM am Anfang der Datei gehört nicht zum JSON
+            let text = if self.line_number == 1 { self.line.trim_start_matches('\u{feff}') } else { &self.line };
+            if text.trim().is_empty() {
+                continue;
+            }
+
+            return Some(
+                serde_json::from_str::<&RawValue>(text)
+                    .and_then(|raw| raw_to_node(raw, &self.options))
+                    .map_err(|e| self.parse_error(text, line_start, e)),
+            );
+        }
+        None
+    }
+}
+
+/// Schreibt jeden Datensatz sofort als eigene Zeile
+struct NdjsonWriter<'a> {
+    output: &'a mut dyn Write,
+    options: JsonOptions,
+}
+
+impl RecordWriter for NdjsonWriter<'_> {
+    fn write_record(&mut self, record: &Node) -> Result<(), FormatError> {
+        let line = record_to_line(record, &self.options)?;
+        writeln!(self.output, "{}", line).map_err(write_error)
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), FormatError> {
+        self.output.flush().map_err(write_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::FileFormat;
+    use crate::stream::convert_stream;
+
+    #[test]
+    fn test_error_reports_line_and_column() {
+        let input = "{\"a\": 1}\n\n{\"a\" 2}\n";
+        let err = read_ndjson(input).unwrap_err();
+        let span = err.span().unwrap();
+        assert_eq!((span.line, span.column, span.offset), (3, 6, 15));
+
+        let FormatError::ParseError { snippet: Some(snippet), .. } = &err else {
+            panic!("snippet erwartet");
+        };
+        assert_eq!(snippet, "  |\n3 | {\"a\" 2}\n  |      ^");
+
+        // Die BOM zählt beim Offset mit, nicht bei der Spalte
+        let input = "\u{feff}{\"a\" 2}\n";
+        let span = read_ndjson(input).unwrap_err().span().unwrap();
+        assert_eq!((span.line, span.column), (1, 6));
+        assert_eq!(&input[span.offset..], "2}\n");
+    }
+
+    #[test]
+    fn test_streaming_matches_whole_document() {
+        let input = "{\"id\": 1, \"tags\": [\"a\"]}\n{\"id\": 2, \"user\": {\"name\": \"x\"}}\n";
+        let options = ConvertOptions::default();
+        for target in [FileFormat::Yaml, FileFormat::Json, FileFormat::Ndjson, FileFormat::Toml] {
+            let mut output = Vec::new();
+            convert_stream(&FileFormat::Ndjson, &target, &mut input.as_bytes(), &mut output, &options).unwrap();
+            let whole = FileFormat::Ndjson.convert(input, target).unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), whole, "{}", target);
+        }
+    }
+
+    #[test]
+    fn test_strict_stops_before_the_record() {
+        let input = "{\"a\": 1}\n{\"a\": 2, \"b\": [1, 2]}\n";
+        let strict = ConvertOptions { strict: true, ..ConvertOptions::default() };
+        let mut output = Vec::new();
+        assert!(convert_stream(&FileFormat::Ndjson, &FileFormat::Csv, &mut input.as_bytes(), &mut output, &strict).is_err());
+        assert_eq!(String::from_utf8(output).unwrap(), "a\n1\n");
+    }
+}
@@ -1,7 +1,453 @@
 // TOML zu anderen Formaten konvertieren
 
 use std::fs;
+use std::path::Path;
+use crate::document::{Comment, Document, Map, Node, Value};
 use crate::error::FormatError;
+use crate::options::{ConvertOptions, NullPolicy, NumberPolicy};
+use crate::registry::{Capabilities, Format};
+use crate::warning::{pointer_push, ConversionWarning, WarningKind};
+use crate::formats::csv::write_csv;
+use crate::formats::json::write_json;
+use crate::formats::toml_editor::TomlEditor;
+use crate::formats::utils::LineIndex;
+use crate::formats::yaml::write_yaml;
+
+// ============================================================================
+// FORMAT-DEFINITION (für die FormatRegistry)
+// ============================================================================
+
+/// TOML als registrierbares Format
+pub struct TomlFormat;
+
+impl Format for TomlFormat {
+    fn name(&self) -> &str {
+        "toml"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["toml"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: true,
+            write: true,
+            nested: true,
+            root_array: false,
+            null: false,
+            datetime: true,
+            tags: false,
+            aliases: false,
+        }
+    }
+
+    fn read(&self, input: &str) -> Result<Document, FormatError> {
+        read_toml(input)
+    }
+
+    fn write(&self, document: &Document) -> Result<String, FormatError> {
+        write_toml(document)
+    }
+
+    fn write_with(&self, document: &Document, options: &ConvertOptions) -> Result<String, FormatError> {
+        write_toml_with(document, options, &mut Vec::new())
+    }
+
+    fn write_with_warnings(
+        &self,
+        document: &Document,
+        options: &ConvertOptions,
+        warnings: &mut Vec<ConversionWarning>,
+    ) -> Result<String, FormatError> {
+        write_toml_with(document, options, warnings)
+    }
+
+    /// TOML → TOML über toml_edit: Kommentare, Leerzeilen und Layout bleiben
+    /// (nur mit `sort_keys` über das Zwischenmodell)
+    fn rewrite_same(&self, input: &str, options: &ConvertOptions) -> Option<Result<String, FormatError>> {
+        (!options.sort_keys).then(|| TomlEditor::parse(input).map(|editor| editor.to_string()))
+    }
+}
+
+// ============================================================================
+// TOML-OPTIONEN
+// ============================================================================
+
+/// Key, unter dem ein Root-Array ohne weitere Angabe abgelegt wird
+pub const DEFAULT_ROOT_KEY: &str = "data";
+
+/// Kommentar in der ersten Zeile, der ein verpacktes Root-Array markiert:
+/// `# asp_cli: root-array = "data"`
+const ROOT_ARRAY_MARKER: &str = "# asp_cli: root-array = ";
+
+/// Einstellungen für die TOML-Ausgabe
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlOptions {
+    /// Key für ein Root-Array, das TOML nicht direkt darstellen kann
+    /// (Arrays von Objekten werden zu `[[key]]`-Tabellen)
+    pub root_key: String,
+    /// Den Namen der Eingabedatei (ohne Endung) als `root_key` verwenden,
+    /// siehe `ConvertOptions::for_input`
+    pub root_key_from_input: bool,
+}
+
+impl Default for TomlOptions {
+    fn default() -> Self {
+        TomlOptions { root_key: DEFAULT_ROOT_KEY.to_string(), root_key_from_input: false }
+    }
+}
+
+// ============================================================================
+// READER / WRITER (Zwischenmodell)
+// ============================================================================
+
+/// Liest einen TOML String in das Zwischenmodell
+///
+/// Verwendet toml_edit, damit Reihenfolge, Datetimes und Quellpositionen erhalten bleiben.
+/// Hat `write_toml` ein Root-Array verpackt (Markierung in der ersten Zeile),
+/// wird es wieder ausgepackt.
+pub fn read_toml(input: &str) -> Result<Document, FormatError> {
+    let doc = toml_edit::ImDocument::parse(input)
+        .map_err(|e| toml_parse_error(input, e.message(), e.span()))?;
+
+    let lines = LineIndex::new(input);
+    let root = table_to_node(doc.as_table(), &lines);
+    Ok(Document::new(unwrap_root_array(input, root)))
+}
+
+/// Packt das Root-Array aus, wenn die Markierung vorhanden ist und die
+/// Tabelle nur noch genau diesen Key enthält
+fn unwrap_root_array(input: &str, root: Node) -> Node {
+    let Some(key) = root_array_marker(input) else {
+        return root;
+    };
+    match root.value {
+        Value::Map(map) if map.len() == 1 && map.get(&key).is_some_and(|n| n.as_array().is_some()) => {
+            map.into_iter().next().map(|(_, node)| node).expect("Map mit einem Eintrag")
+        }
+        value => Node { value, ..root },
+    }
+}
+
+/// Key aus der Markierung `# asp_cli: root-array = "<key>"` in der ersten Zeile
+fn root_array_marker(input: &str) -> Option<String> {
+    let rest = input.lines().next()?.trim_end().strip_prefix(ROOT_ARRAY_MARKER)?;
+    // Wert als TOML-String parsen, damit Escapes im Key stimmen
+    let table: toml::Table = format!("key = {}", rest).parse().ok()?;
+    table.get("key")?.as_str().map(str::to_string)
+}
+
+/// Schreibt das Zwischenmodell als TOML String
+///
+/// TOML unterstützt kein Array als Root-Element - Arrays werden in ein
+/// Objekt mit dem Key "data" gewrappt und in der ersten Zeile markiert,
+/// damit `read_toml` sie wieder auspackt. null-Werte werden ausgelassen
+/// (siehe `write_toml_with` für andere Varianten).
+pub fn write_toml(doc: &Document) -> Result<String, FormatError> {
+    write_toml_with(doc, &ConvertOptions::default(), &mut Vec::new())
+}
+
+/// Wie `write_toml`, mit `NullPolicy`, `NumberPolicy` und `TomlOptions` aus den Optionen
+///
+/// Ausgelassene null-Werte und nicht darstellbare Zahlen werden mit ihrem
+/// JSON-Pointer in `warnings` gemeldet.
+pub fn write_toml_with(
+    doc: &Document,
+    options: &ConvertOptions,
+    warnings: &mut Vec<ConversionWarning>,
+) -> Result<String, FormatError> {
+    let mut writer = TomlWriter { null_policy: &options.null_policy, number_policy: options.number_policy, warnings };
+
+    let toml_value = match &doc.root.value {
+        Value::Array(_) => {
+            let key = &options.toml.root_key;
+            writer.warnings.push(ConversionWarning::new(
+                WarningKind::RootWrapped,
+                "",
+                format!("Root-Array in Tabelle {:?} verpackt (TOML braucht ein Objekt als Root)", key),
+            ));
+            let mut root = toml::map::Map::new();
+            if let Some(data) = writer.node_to_toml_value(&doc.root, &pointer_push("", key))? {
+                root.insert(key.clone(), data);
+            }
+            let body = toml::to_string_pretty(&toml::Value::Table(root))
+                .map_err(|e| FormatError::SerializationError(format!("Fehler beim Serialisieren von TOML: {}", e)))?;
+            let body = if has_comments(&doc.root) {
+                attach_comments(body, |table| comments_into_entry(table, key, None, &doc.root))
+            } else {
+                body
+            };
+            return Ok(format!("{}{}\n{}", ROOT_ARRAY_MARKER, toml::Value::String(key.clone()), body));
+        }
+        Value::Map(_) => writer
+            .node_to_toml_value(&doc.root, "")?
+            .unwrap_or_else(|| toml::Value::Table(toml::map::Map::new())),
+        other => {
+            return Err(FormatError::SerializationError(format!(
+                "TOML benötigt ein Objekt als Root, gefunden: {}",
+                other.type_name()
+            )))
+        }
+    };
+
+    let text = toml::to_string_pretty(&toml_value)
+        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Serialisieren von TOML: {}", e)))?;
+
+    match &doc.root.value {
+        Value::Map(map) if has_comments(&doc.root) => Ok(attach_comments(text, |table| comments_into_table(table, map))),
+        _ => Ok(text),
+    }
+}
+
+// ============================================================================
+// KOMMENTARE (z.B. aus YAML)
+// ============================================================================
+
+fn has_comments(node: &Node) -> bool {
+    match &node.value {
+        Value::Array(items) => items.iter().any(has_comments),
+        Value::Map(map) => map.iter().any(|(key, value)| key.comment.is_some() || has_comments(value)),
+        _ => false,
+    }
+}
+
+/// Überträgt Kommentare von Map-Keys in das fertige TOML
+///
+/// Kommentare landen über bzw. hinter `key = value` und `[table]`-Headern.
+/// Für Werte in Inline-Tabellen und Arrays gibt es keinen sinnvollen Platz,
+/// dort fallen sie weg.
+fn attach_comments(text: String, attach: impl FnOnce(&mut toml_edit::Table)) -> String {
+    match text.parse::<toml_edit::DocumentMut>() {
+        Ok(mut doc) => {
+            attach(doc.as_table_mut());
+            doc.to_string()
+        }
+        Err(_) => text,
+    }
+}
+
+fn comments_into_table(table: &mut toml_edit::Table, map: &Map) {
+    for (key, value) in map.iter() {
+        comments_into_entry(table, &key.to_key_string(), key.comment.as_deref(), value);
+    }
+}
+
+/// Kommentar des Keys `name` und alle Kommentare in seinem Wert
+fn comments_into_entry(table: &mut toml_edit::Table, name: &str, comment: Option<&Comment>, value: &Node) {
+    if let Some(comment) = comment {
+        let before: String = comment.before.iter().map(|line| format!("#{}\n", line)).collect();
+        let trailing = comment.trailing.as_ref().map(|text| format!(" #{}", text));
+
+        match table.get_mut(name) {
+            Some(toml_edit::Item::Table(child)) => {
+                prepend_prefix(child.decor_mut(), &before);
+                if let Some(trailing) = trailing {
+                    child.decor_mut().set_suffix(trailing);
+                }
+            }
+            Some(toml_edit::Item::ArrayOfTables(tables)) => {
+                if let Some(first) = tables.get_mut(0) {
+                    prepend_prefix(first.decor_mut(), &before);
+                }
+            }
+            Some(toml_edit::Item::Value(inline)) => {
+                if let Some(trailing) = trailing {
+                    inline.decor_mut().set_suffix(trailing);
+                }
+                if let Some(mut key) = table.key_mut(name) {
+                    prepend_prefix(key.leaf_decor_mut(), &before);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    match (table.get_mut(name), &value.value) {
+        (Some(toml_edit::Item::Table(child)), Value::Map(child_map)) => comments_into_table(child, child_map),
+        (Some(toml_edit::Item::ArrayOfTables(tables)), Value::Array(items)) => {
+            let maps = items.iter().filter_map(Node::as_map);
+            for (child, child_map) in tables.iter_mut().zip(maps) {
+                comments_into_table(child, child_map);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn prepend_prefix(decor: &mut toml_edit::Decor, lines: &str) {
+    if lines.is_empty() {
+        return;
+    }
+    let existing = decor.prefix().and_then(|p| p.as_str()).unwrap_or_default().to_string();
+    decor.set_prefix(format!("{}{}", existing, lines));
+}
+
+/// TOML-Fehler (toml oder toml_edit) → ParseError mit Position und Ausschnitt
+pub(crate) fn toml_parse_error(input: &str, message: &str, span: Option<std::ops::Range<usize>>) -> FormatError {
+    let span = span.map(|range| LineIndex::new(input).span_of_range(range));
+    // toml_edit liefert mehrzeilige Meldungen ("invalid string\nexpected ...")
+    let message = message.trim_end().replace('\n', ", ");
+    FormatError::parse("toml", message, input, span)
+}
+
+fn item_to_node(item: &toml_edit::Item, lines: &LineIndex) -> Node {
+    match item {
+        toml_edit::Item::Value(value) => value_to_node(value, lines),
+        toml_edit::Item::Table(table) => table_to_node(table, lines),
+        toml_edit::Item::ArrayOfTables(tables) => {
+            let items = tables.iter().map(|t| table_to_node(t, lines)).collect();
+            Node::array(items).with_span(tables.span().map(|r| lines.span_of_range(r)))
+        }
+        toml_edit::Item::None => Node::null(),
+    }
+}
+
+fn table_to_node(table: &toml_edit::Table, lines: &LineIndex) -> Node {
+    let mut map = Map::new();
+    for (key, item) in table.iter() {
+        if item.is_none() {
+            continue;
+        }
+        let key_span = table
+            .get_key_value(key)
+            .and_then(|(k, _)| k.span())
+            .map(|r| lines.span_of_range(r));
+        // TOML erlaubt keine doppelten Keys, push genügt
+        map.push(Node::string(key).with_span(key_span), item_to_node(item, lines));
+    }
+    Node::map(map).with_span(table.span().map(|r| lines.span_of_range(r)))
+}
+
+fn value_to_node(value: &toml_edit::Value, lines: &LineIndex) -> Node {
+    let node_value = match value {
+        toml_edit::Value::String(s) => Value::String(s.value().clone()),
+        toml_edit::Value::Integer(i) => Value::Integer(*i.value()),
+        toml_edit::Value::Float(f) => Value::Float(*f.value()),
+        toml_edit::Value::Boolean(b) => Value::Bool(*b.value()),
+        toml_edit::Value::Datetime(dt) => Value::DateTime(*dt.value()),
+        toml_edit::Value::Array(arr) => Value::Array(arr.iter().map(|v| value_to_node(v, lines)).collect()),
+        toml_edit::Value::InlineTable(table) => {
+            let mut map = Map::new();
+            for (key, val) in table.iter() {
+                map.push(Node::string(key), value_to_node(val, lines));
+            }
+            Value::Map(map)
+        }
+    };
+    Node::new(node_value).with_span(value.span().map(|r| lines.span_of_range(r)))
+}
+
+/// Einzelner Knoten → toml::Value mit `NullPolicy` und `NumberPolicy` aus den Optionen
+/// (None = null-Wert ausgelassen)
+pub(crate) fn node_to_toml(
+    node: &Node,
+    options: &ConvertOptions,
+    warnings: &mut Vec<ConversionWarning>,
+) -> Result<Option<toml::Value>, FormatError> {
+    TomlWriter { null_policy: &options.null_policy, number_policy: options.number_policy, warnings }.node_to_toml_value(node, "")
+}
+
+/// Zustand beim Schreiben: null- und Zahlen-Behandlung, gesammelte Warnungen
+struct TomlWriter<'a> {
+    null_policy: &'a NullPolicy,
+    number_policy: NumberPolicy,
+    warnings: &'a mut Vec<ConversionWarning>,
+}
+
+impl TomlWriter<'_> {
+    /// Node → toml::Value (None = null-Wert wird ausgelassen)
+    ///
+    /// `path` ist der JSON-Pointer des Knotens für Warnungen und Fehler.
+    fn node_to_toml_value(&mut self, node: &Node, path: &str) -> Result<Option<toml::Value>, FormatError> {
+        let value = match &node.value {
+            // TOML hat kein echtes "null"
+            Value::Null => return self.null_to_toml(path),
+            Value::Bool(b) => toml::Value::Boolean(*b),
+            Value::Integer(i) => toml::Value::Integer(*i),
+            Value::BigInt(s) | Value::Decimal(s) => self.number_to_toml(&node.value, s, path)?,
+            Value::Float(f) => toml::Value::Float(*f),
+            Value::DateTime(dt) => toml::Value::Datetime(*dt),
+            Value::String(_) | Value::Bytes(_) => toml::Value::String(
+                crate::document::scalar_to_string(&node.value).unwrap_or_default(),
+            ),
+            Value::Array(items) => {
+                // Rekursiv: Jedes Array-Element konvertieren, ausgelassene fallen weg
+                let mut toml_arr = Vec::with_capacity(items.len());
+                for (i, item) in items.iter().enumerate() {
+                    if let Some(value) = self.node_to_toml_value(item, &pointer_push(path, &i.to_string()))? {
+                        toml_arr.push(value);
+                    }
+                }
+                toml::Value::Array(toml_arr)
+            }
+            Value::Map(map) => {
+                // Rekursiv: Jeden Objekt-Wert konvertieren
+                let mut toml_table = toml::map::Map::new();
+                for (key, value) in map.iter() {
+                    let key = key.to_key_string();
+                    if let Some(value) = self.node_to_toml_value(value, &pointer_push(path, &key))? {
+                        toml_table.insert(key, value);
+                    }
+                }
+                toml::Value::Table(toml_table)
+            }
+        };
+        Ok(Some(value))
+    }
+
+    fn null_to_toml(&mut self, path: &str) -> Result<Option<toml::Value>, FormatError> {
+        match self.null_policy {
+            NullPolicy::Omit => {
+                self.warnings.push(ConversionWarning::new(
+                    WarningKind::NullOmitted,
+                    path,
+                    "null-Wert ausgelassen (TOML kennt kein null)",
+                ));
+                Ok(None)
+            }
+            NullPolicy::EmptyString => Ok(Some(toml::Value::String(String::new()))),
+            NullPolicy::Sentinel(text) => Ok(Some(toml::Value::String(text.clone()))),
+            NullPolicy::Error => Err(FormatError::SerializationError(format!(
+                "null-Wert bei {} - TOML kennt kein null",
+                if path.is_empty() { "/" } else { path }
+            ))),
+        }
+    }
+
+    /// Ganzzahl ausserhalb von i64 bzw. Dezimalzahl mit mehr Stellen als f64
+    fn number_to_toml(&mut self, value: &Value, text: &str, path: &str) -> Result<toml::Value, FormatError> {
+        let (result, message) = match self.number_policy {
+            NumberPolicy::Stringify => (
+                toml::Value::String(text.to_string()),
+                format!("Zahl {} als String geschrieben (TOML kann sie nicht exakt darstellen)", text),
+            ),
+            NumberPolicy::Clamp => {
+                let clamped = match value {
+                    Value::BigInt(_) if text.starts_with('-') => toml::Value::Integer(i64::MIN),
+                    Value::BigInt(_) => toml::Value::Integer(i64::MAX),
+                    _ => toml::Value::Float(text.parse::<f64>().map(clamp_float).unwrap_or(f64::NAN)),
+                };
+                let message = format!("Zahl {} auf {} gerundet (TOML kann sie nicht exakt darstellen)", text, clamped);
+                (clamped, message)
+            }
+            NumberPolicy::Error => {
+                return Err(FormatError::SerializationError(format!(
+                    "Zahl {} bei {} kann in TOML nicht exakt dargestellt werden",
+                    text,
+                    if path.is_empty() { "/" } else { path }
+                )));
+            }
+        };
+        self.warnings.push(ConversionWarning::new(WarningKind::NumberOutOfRange, path, message));
+        Ok(result)
+    }
+}
+
+/// ±Unendlich (Überlauf) → grösster endlicher f64
+fn clamp_float(f: f64) -> f64 {
+    if f.is_infinite() { f64::MAX.copysign(f) } else { f }
+}
 
 // ============================================================================
 // STRING-ZU-STRING FUNKTIONEN (Core-Logik für CLI und Web)
@@ -9,99 +455,23 @@ use crate::error::FormatError;
 
 /// Konvertiert TOML String zu JSON String
 pub fn toml_to_json_string(input: &str) -> Result<String, FormatError> {
-    let toml_value: toml::Value = toml::from_str(input)
-        .map_err(|e| FormatError::ParseError(format!("Ungültiges TOML: {}", e)))?;
-    
-    let json_value = serde_json::to_value(&toml_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Konvertieren: {}", e)))?;
-    
-    serde_json::to_string_pretty(&json_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von JSON: {}", e)))
+    write_json(&read_toml(input)?)
 }
 
 /// Konvertiert TOML String zu YAML String
 pub fn toml_to_yaml_string(input: &str) -> Result<String, FormatError> {
-    let toml_value: toml::Value = toml::from_str(input)
-        .map_err(|e| FormatError::ParseError(format!("Ungültiges TOML: {}", e)))?;
-    
-    let json_value = serde_json::to_value(&toml_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Konvertieren: {}", e)))?;
-    
-    serde_yaml::to_string(&json_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von YAML: {}", e)))
+    write_yaml(&read_toml(input)?)
 }
 
-/// Konvertiert TOML String zu TOML String (Pretty-Printing)
+/// Konvertiert TOML String zu TOML String (Validierung, Kommentare und
+/// Layout bleiben unverändert)
 pub fn toml_to_toml_string(input: &str) -> Result<String, FormatError> {
-    let toml_value: toml::Value = toml::from_str(input)
-        .map_err(|e| FormatError::ParseError(format!("Ungültiges TOML: {}", e)))?;
-    
-    toml::to_string_pretty(&toml_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von TOML: {}", e)))
+    TomlEditor::parse(input).map(|editor| editor.to_string())
 }
 
 /// Konvertiert TOML String zu CSV String
 pub fn toml_to_csv_string(input: &str) -> Result<String, FormatError> {
-    let toml_value: toml::Value = toml::from_str(input)
-        .map_err(|e| FormatError::ParseError(format!("Ungültiges TOML: {}", e)))?;
-    
-    let mut json_value = serde_json::to_value(&toml_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Konvertieren: {}", e)))?;
-    
-    // Wenn es ein Objekt mit "data" Key ist (von CSV → TOML), extrahiere das Array
-    if let serde_json::Value::Object(ref obj) = json_value {
-        if let Some(data_value) = obj.get("data") {
-            json_value = data_value.clone();
-        }
-    }
-    
-    // Prüfen ob es ein Array ist
-    let array = match json_value {
-        serde_json::Value::Array(arr) => arr,
-        serde_json::Value::Object(_) => vec![json_value],
-        _ => return Err(FormatError::SerializationError("TOML muss ein Array oder Objekt sein".to_string()))
-    };
-    
-    if array.is_empty() {
-        return Ok(String::new());
-    }
-    
-    // Alle Objekte flattenen
-    let flattened: Vec<_> = array.iter()
-        .map(|v| flatten_json_value(v, ""))
-        .collect();
-    
-    // Header sammeln
-    let mut all_headers = std::collections::BTreeSet::new();
-    for obj in &flattened {
-        for key in obj.keys() {
-            all_headers.insert(key.clone());
-        }
-    }
-    let headers: Vec<String> = all_headers.into_iter().collect();
-    
-    // CSV Writer in Memory
-    let mut writer = csv::Writer::from_writer(vec![]);
-    
-    // Header schreiben
-    writer.write_record(&headers)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Schreiben der CSV-Header: {}", e)))?;
-    
-    // Daten schreiben
-    for flat_obj in flattened {
-        let row: Vec<String> = headers.iter()
-            .map(|h| flat_obj.get(h).cloned().unwrap_or_default())
-            .collect();
-        writer.write_record(&row)
-            .map_err(|e| FormatError::SerializationError(format!("Fehler beim Schreiben der CSV-Zeile: {}", e)))?;
-    }
-    
-    // Writer in String umwandeln
-    let data = writer.into_inner()
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Abschliessen von CSV: {}", e)))?;
-    
-    String::from_utf8(data)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler bei UTF-8 Konvertierung: {}", e)))
+    write_csv(&read_toml(input)?)
 }
 
 // ============================================================================
@@ -110,134 +480,175 @@ pub fn toml_to_csv_string(input: &str) -> Result<String, FormatError> {
 
 /// Konvertiert TOML zu JSON (File-I/O Wrapper)
 pub fn convert_toml_to_json(
-    input_path: &str,
-    output_path: &str
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = toml_to_json_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
 
 /// Konvertiert TOML zu YAML (File-I/O Wrapper)
 pub fn convert_toml_to_yaml(
-    input_path: &str,
-    output_path: &str
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = toml_to_yaml_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
 
 /// Konvertiert TOML zu TOML (File-I/O Wrapper)
 pub fn convert_toml_to_toml(
-    input_path: &str,
-    output_path: &str
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = toml_to_toml_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
 
 /// Konvertiert TOML zu CSV (File-I/O Wrapper)
 pub fn convert_toml_to_csv(
-    input_path: &str,
-    output_path: &str
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = toml_to_csv_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
 
-/// Flattened ein JSON-Objekt zu einer flachen Map mit Unterstrich-Trennzeichen
-/// 
-/// Beispiel:
-/// Input:  {"contact": {"email": "test@test.com", "phone": "+49"}}
-/// Output: {"contact_email": "test@test.com", "contact_phone": "+49"}
-fn flatten_json_value(value: &serde_json::Value, prefix: &str) -> std::collections::HashMap<String, String> {
-    use std::collections::HashMap;
-    
-    let mut result = HashMap::new();
-    
-    match value {
-        serde_json::Value::Object(obj) => {
-            for (key, val) in obj {
-                let new_key = if prefix.is_empty() {
-                    key.clone()
-                } else {
-                    format!("{}_{}", prefix, key)
-                };
-                
-                // Rekursiv für verschachtelte Objekte
-                if val.is_object() {
-                    let nested = flatten_json_value(val, &new_key);
-                    result.extend(nested);
-                } else {
-                    result.insert(new_key, json_value_to_string(val));
-                }
-            }
-        }
-        _ => {
-            // Primitive Werte direkt einfügen
-            if !prefix.is_empty() {
-                result.insert(prefix.to_string(), json_value_to_string(value));
-            }
-        }
-    }
-    
-    result
-}
-
-/// Konvertiert einen JSON-Wert zu einem String
-fn json_value_to_string(value: &serde_json::Value) -> String {
-    match value {
-        serde_json::Value::String(s) => s.clone(),
-        serde_json::Value::Number(n) => n.to_string(),
-        serde_json::Value::Bool(b) => b.to_string(),
-        serde_json::Value::Null => String::new(),
-        _ => serde_json::to_string(value).unwrap_or_default(),
-    }
-}
-
 /// Validiert eine TOML-Datei ohne sie zu schreiben
 /// Ähnlich wie validate_json, aber für TOML
 /// 
-/// Diese Funktion ist zentral für alle TOML-Konvertierungen:
-/// Sie liest die Datei, parst sie und gibt ein toml::Value zurück
-pub fn validate_toml(input_path: &str) -> Result<toml::Value, FormatError> {
+/// Sie liest die Datei, parst sie mit `read_toml` und gibt ein toml::Value zurück
+pub fn validate_toml(input_path: impl AsRef<Path>) -> Result<toml::Value, FormatError> {
+    let input_path = input_path.as_ref();
     // 1. Datei lesen - content enthält den TOML-Text als String
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
+
+    // 2. TOML mit demselben Reader wie bei der Konvertierung parsen
+    let doc = read_toml(&content)?;
+
+    // 3. Validiertes toml::Value zurückgeben (TOML enthält kein null)
+    let mut writer = TomlWriter {
+        null_policy: &NullPolicy::Omit,
+        number_policy: NumberPolicy::Error,
+        warnings: &mut Vec::new(),
+    };
+    Ok(writer
+        .node_to_toml_value(&doc.root, "")?
+        .unwrap_or_else(|| toml::Value::Table(toml::map::Map::new())))
+}
 
-    // 2. TOML parsen und validieren
-    // toml::from_str wandelt den String in eine toml::Value Struktur um
-    // Dies ist die "Intermediate Representation" (IR)
-    let toml_value: toml::Value = toml::from_str(&content)
-        .map_err(|e| FormatError::ParseError(format!("Fehler beim Parsen von TOML: {}", e)))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::json::read_json;
 
-    // 3. Validiertes toml::Value zurückgeben
-    Ok(toml_value)
-}
\ No newline at end of file
+    fn write(input: &str, null_policy: NullPolicy) -> (Result<String, FormatError>, Vec<ConversionWarning>) {
+        let doc = read_json(input).unwrap();
+        let options = ConvertOptions { null_policy, ..ConvertOptions::default() };
+        let mut warnings = Vec::new();
+        (write_toml_with(&doc, &options, &mut warnings), warnings)
+    }
+
+    #[test]
+    fn test_null_omitted_with_paths() {
+        let (out, warnings) = write(r#"{"a": null, "b": {"c/d": null, "e": [1, null]}}"#, NullPolicy::Omit);
+        assert_eq!(out.unwrap(), "[b]\ne = [1]\n");
+        let paths: Vec<&str> = warnings.iter().map(|w| w.path.as_str()).collect();
+        assert_eq!(paths, ["/a", "/b/c~1d", "/b/e/1"]);
+    }
+
+    #[test]
+    fn test_null_sentinel_and_error() {
+        let (out, _) = write(r#"{"a": null}"#, NullPolicy::Sentinel("NULL".to_string()));
+        assert_eq!(out.unwrap(), "a = \"NULL\"\n");
+
+        let (out, _) = write(r#"{"a": {"b": null}}"#, NullPolicy::Error);
+        assert!(out.unwrap_err().to_string().contains("/a/b"));
+    }
+
+    #[test]
+    fn test_big_number_policies() {
+        let input = r#"{"id": 18446744073709551616, "n": 1}"#;
+        let doc = read_json(input).unwrap();
+        let write = |number_policy| {
+            let options = ConvertOptions { number_policy, ..ConvertOptions::default() };
+            let mut warnings = Vec::new();
+            (write_toml_with(&doc, &options, &mut warnings), warnings)
+        };
+
+        let (out, warnings) = write(NumberPolicy::Stringify);
+        assert_eq!(out.unwrap(), "id = \"18446744073709551616\"\nn = 1\n");
+        assert_eq!(warnings[0].kind, WarningKind::NumberOutOfRange);
+        assert_eq!(warnings[0].path, "/id");
+
+        let (out, _) = write(NumberPolicy::Clamp);
+        assert_eq!(out.unwrap(), "id = 9223372036854775807\nn = 1\n");
+
+        let (out, _) = write(NumberPolicy::Error);
+        assert!(out.unwrap_err().to_string().contains("/id"));
+    }
+
+    #[test]
+    fn test_root_array_round_trip() {
+        let options = ConvertOptions {
+            toml: TomlOptions { root_key: "items".to_string(), ..TomlOptions::default() },
+            ..ConvertOptions::default()
+        };
+        let doc = read_json(r#"[{"a": 1}]"#).unwrap();
+        let toml = write_toml_with(&doc, &options, &mut Vec::new()).unwrap();
+        assert_eq!(toml, "# asp_cli: root-array = \"items\"\n[[items]]\na = 1\n");
+        assert_eq!(read_toml(&toml).unwrap().root.as_array().map(Vec::len), Some(1));
+
+        // Ohne Markierung bleibt ein echtes "data"-Feld erhalten
+        let doc = read_toml("[[data]]\na = 1\n").unwrap();
+        assert!(doc.root.as_map().is_some_and(|m| m.get("data").is_some()));
+        let toml = write_toml(&read_json(r#"{"data": [{"a": 1}]}"#).unwrap()).unwrap();
+        assert_eq!(toml, "[[data]]\na = 1\n");
+        assert!(read_toml(&toml).unwrap().root.as_map().is_some_and(|m| m.len() == 1 && m.get("data").is_some()));
+    }
+
+    #[test]
+    fn test_root_array_keeps_comments() {
+        let doc = crate::formats::yaml::read_yaml("- name: a # Name
+  # Port
+  port: 80
+").unwrap();
+        assert_eq!(
+            write_toml(&doc).unwrap(),
+            "# asp_cli: root-array = \"data\"\n[[data]]\nname = \"a\" # Name\n# Port\nport = 80\n"
+        );
+    }
+}
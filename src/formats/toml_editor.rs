@@ -0,0 +1,265 @@
+// Verlustfreie TOML-Bearbeitung
+//
+// Arbeitet direkt auf dem toml_edit-Dokument statt über das Zwischenmodell:
+// Kommentare, Leerzeilen, Reihenfolge und Tabellen-Layout bleiben erhalten,
+// unberührte Abschnitte werden Byte für Byte wieder ausgegeben. Geänderte
+// Werte behalten ihre Kommentare (z.B. `port = 8080  # Standard`).
+
+use std::fmt;
+
+use toml_edit::{ArrayOfTables, DocumentMut, InlineTable, Item, Key, Table, TableLike};
+
+use crate::document::{Document, Node, Value};
+use crate::error::FormatError;
+use crate::formats::toml::{node_to_toml, toml_parse_error};
+use crate::options::ConvertOptions;
+
+/// Ein TOML-Dokument, das gezielt geändert werden kann
+///
+/// # Beispiel
+/// ```
+/// use asp_cli::formats::toml_editor::TomlEditor;
+/// use asp_cli::Node;
+///
+/// let mut editor = TomlEditor::parse("# Server\n[server]\nport = 80  # alt\n")?;
+/// editor.set("server.port", &Node::new(asp_cli::Value::Integer(8080)))?;
+/// assert_eq!(editor.to_string(), "# Server\n[server]\nport = 8080  # alt\n");
+/// # Ok::<(), asp_cli::FormatError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct TomlEditor {
+    doc: DocumentMut,
+}
+
+impl TomlEditor {
+    pub fn parse(input: &str) -> Result<Self, FormatError> {
+        let doc = input
+            .parse::<DocumentMut>()
+            .map_err(|e| toml_parse_error(input, e.message(), e.span()))?;
+        Ok(TomlEditor { doc })
+    }
+
+    /// Setzt einen Wert über einen Punkt-Pfad wie `server.port` oder
+    /// `tool."black.line"`; fehlende Tabellen werden angelegt
+    ///
+    /// Objekte werden zu `[table]`-Abschnitten, ausser der alte Wert war
+    /// inline - dann bleibt es eine Inline-Tabelle.
+    pub fn set(&mut self, path: &str, value: &Node) -> Result<(), FormatError> {
+        if value.is_null() {
+            return Err(FormatError::SerializationError(format!(
+                "null kann in TOML nicht gesetzt werden ({}) - Key stattdessen entfernen",
+                path
+            )));
+        }
+        let (parents, key) = parse_path(path)?;
+        let table = navigate(self.doc.as_table_mut(), &parents, path)?;
+        let item = node_to_item(value)?;
+        match table.get_mut(key.get()) {
+            Some(slot) => replace_keeping_decor(slot, item),
+            None => {
+                table.insert(key.get(), item);
+            }
+        }
+        Ok(())
+    }
+
+    /// Entfernt einen Key; `false`, wenn es ihn nicht gab
+    pub fn remove(&mut self, path: &str) -> Result<bool, FormatError> {
+        let (parents, key) = parse_path(path)?;
+        let mut table: &mut dyn TableLike = self.doc.as_table_mut();
+        for parent in &parents {
+            match table.get_mut(parent.get()).and_then(Item::as_table_like_mut) {
+                Some(child) => table = child,
+                None => return Ok(false),
+            }
+        }
+        Ok(table.remove(key.get()).is_some())
+    }
+
+    /// Führt ein Dokument (aus beliebigem Format) in dieses TOML ein
+    ///
+    /// Tabellen werden rekursiv zusammengeführt, alle anderen Werte ersetzt.
+    /// null-Werte im Overlay werden ignoriert.
+    pub fn merge(&mut self, overlay: &Document) -> Result<(), FormatError> {
+        if !matches!(overlay.root.value, Value::Map(_)) {
+            return Err(FormatError::SerializationError(format!(
+                "Merge benötigt ein Objekt als Root, gefunden: {}",
+                overlay.root.value.type_name()
+            )));
+        }
+        if let Item::Table(source) = node_to_item(&overlay.root)? {
+            merge_table(self.doc.as_table_mut(), source);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TomlEditor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.doc)
+    }
+}
+
+/// Punkt-Pfad → (Eltern-Keys, letzter Key)
+fn parse_path(path: &str) -> Result<(Vec<Key>, Key), FormatError> {
+    let mut keys = Key::parse(path)
+        .map_err(|e| FormatError::InvalidFormat(format!("Ungültiger Key-Pfad {:?}: {}", path, e.message().trim())))?;
+    let key = keys
+        .pop()
+        .ok_or_else(|| FormatError::InvalidFormat("Leerer Key-Pfad".to_string()))?;
+    Ok((keys, key))
+}
+
+/// Läuft den Pfad entlang und legt fehlende Tabellen implizit an
+/// (ohne eigenen `[header]`, solange sie nur Untertabellen enthalten)
+fn navigate<'a>(mut table: &'a mut dyn TableLike, parents: &[Key], path: &str) -> Result<&'a mut dyn TableLike, FormatError> {
+    for parent in parents {
+        if table.get(parent.get()).is_none() {
+            let mut child = Table::new();
+            child.set_implicit(true);
+            table.insert(parent.get(), Item::Table(child));
+        }
+        table = table
+            .get_mut(parent.get())
+            .and_then(Item::as_table_like_mut)
+            .ok_or_else(|| {
+                FormatError::InvalidFormat(format!("{} in {} ist keine Tabelle", parent.get(), path))
+            })?;
+    }
+    Ok(table)
+}
+
+fn merge_table(target: &mut dyn TableLike, source: Table) {
+    for (key, item) in source {
+        match target.get_mut(&key) {
+            Some(slot) => match (slot.is_table_like(), item) {
+                (true, Item::Table(child)) => {
+                    if let Some(slot) = slot.as_table_like_mut() {
+                        merge_table(slot, child);
+                    }
+                }
+                (_, item) => replace_keeping_decor(slot, item),
+            },
+            None => {
+                target.insert(&key, item);
+            }
+        }
+    }
+}
+
+/// Ersetzt einen Eintrag und übernimmt Kommentare/Abstände des alten Werts
+fn replace_keeping_decor(slot: &mut Item, mut item: Item) {
+    // Inline bleibt inline, damit das Layout der Datei erhalten bleibt
+    if slot.is_value() {
+        item.make_value();
+    }
+    match (&*slot, &mut item) {
+        (Item::Value(old), Item::Value(new)) => *new.decor_mut() = old.decor().clone(),
+        (Item::Table(old), Item::Table(new)) => {
+            *new.decor_mut() = old.decor().clone();
+            if let Some(position) = old.position() {
+                new.set_position(position);
+            }
+        }
+        _ => {}
+    }
+    *slot = item;
+}
+
+/// Node → toml_edit::Item (über dieselbe Abbildung wie `write_toml`)
+fn node_to_item(node: &Node) -> Result<Item, FormatError> {
+    let value = node_to_toml(node, &ConvertOptions::default(), &mut Vec::new())?;
+    Ok(value.map(toml_to_item).unwrap_or(Item::None))
+}
+
+fn toml_to_item(value: toml::Value) -> Item {
+    match value {
+        toml::Value::Table(entries) => {
+            let mut table = Table::new();
+            for (key, value) in entries {
+                table.insert(&key, toml_to_item(value));
+            }
+            Item::Table(table)
+        }
+        toml::Value::Array(items) if !items.is_empty() && items.iter().all(toml::Value::is_table) => {
+            let mut tables = ArrayOfTables::new();
+            for item in items {
+                if let Item::Table(table) = toml_to_item(item) {
+                    tables.push(table);
+                }
+            }
+            Item::ArrayOfTables(tables)
+        }
+        other => Item::Value(toml_to_value(other)),
+    }
+}
+
+fn toml_to_value(value: toml::Value) -> toml_edit::Value {
+    match value {
+        toml::Value::String(s) => s.into(),
+        toml::Value::Integer(i) => i.into(),
+        toml::Value::Float(f) => f.into(),
+        toml::Value::Boolean(b) => b.into(),
+        toml::Value::Datetime(dt) => dt.into(),
+        toml::Value::Array(items) => toml_edit::Value::Array(items.into_iter().map(toml_to_value).collect()),
+        toml::Value::Table(entries) => toml_edit::Value::InlineTable(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k, toml_to_value(v)))
+                .collect::<InlineTable>(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::json::read_json;
+
+    const CONFIG: &str = "\
+# Hand gepflegt
+title = \"app\"   # Name
+
+[server]
+host = \"localhost\"
+port = 80 # alt
+
+# Datenbank
+[db]
+url = \"postgres://\"
+";
+
+    #[test]
+    fn test_untouched_sections_stay_identical() {
+        let mut editor = TomlEditor::parse(CONFIG).unwrap();
+        assert_eq!(editor.to_string(), CONFIG);
+
+        editor.merge(&read_json(r#"{"server": {"port": 8080, "tls": true}}"#).unwrap()).unwrap();
+        assert!(editor.remove("server.host").unwrap());
+        assert!(!editor.remove("missing.key").unwrap());
+
+        assert_eq!(
+            editor.to_string(),
+            "\
+# Hand gepflegt
+title = \"app\"   # Name
+
+[server]
+port = 8080 # alt
+tls = true
+
+# Datenbank
+[db]
+url = \"postgres://\"
+"
+        );
+    }
+
+    #[test]
+    fn test_set_creates_tables() {
+        let mut editor = TomlEditor::parse("a = 1\n").unwrap();
+        editor.set("tool.\"black.cfg\".line", &Node::new(Value::Integer(88))).unwrap();
+        assert_eq!(editor.to_string(), "a = 1\n\n[tool.\"black.cfg\"]\nline = 88\n");
+        assert!(editor.set("a.b", &Node::string("x")).is_err());
+    }
+}
@@ -1,45 +1,124 @@
 // Gemeinsame Helper-Funktionen für Format-Konvertierungen
 
-use crate::error::FormatError;
-use serde_json::Value as JsonValue;
-
-
-
-pub fn json_to_toml_value(json: &JsonValue) -> Result<toml::Value, FormatError> {
-    match json {
-        JsonValue::Null => {
-            // TOML hat kein echtes "null" - wir verwenden einen leeren String als Ersatz
-            Ok(toml::Value::String(String::new()))
-        },
-        JsonValue::Bool(b) => Ok(toml::Value::Boolean(*b)),
-        JsonValue::Number(n) => {
-            if let Some(i) = n.as_i64() {
-                Ok(toml::Value::Integer(i))
-            } else if let Some(f) = n.as_f64() {
-                Ok(toml::Value::Float(f))
+use crate::document::{DateTime, Span};
+
+/// Index der Zeilenanfänge einer Eingabe, um Byte-Offsets in
+/// Zeile/Spalte umzurechnen (und umgekehrt)
+pub(crate) struct LineIndex<'a> {
+    input: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(input.match_indices('\n').map(|(i, _)| i + 1));
+        LineIndex { input, line_starts }
+    }
+
+    /// Span ab einem Byte-Offset
+    pub(crate) fn span(&self, offset: usize, len: usize) -> Span {
+        let offset = offset.min(self.input.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self.input[line_start..offset].chars().count() + 1;
+        Span { offset, len, line, column }
+    }
+
+    /// Span aus einem Byte-Bereich (z.B. von toml_edit)
+    pub(crate) fn span_of_range(&self, range: std::ops::Range<usize>) -> Span {
+        self.span(range.start, range.end.saturating_sub(range.start))
+    }
+
+    /// Span aus Zeile (1-basiert) und Spalte in Bytes (1-basiert, wie bei serde_json)
+    pub(crate) fn span_at_byte_column(&self, line: usize, column: usize) -> Span {
+        let Some(&line_start) = self.line_starts.get(line.saturating_sub(1)) else {
+            return self.span(self.input.len(), 0);
+        };
+        let mut offset = (line_start + column.saturating_sub(1)).min(self.input.len());
+        while !self.input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let len = self.input[offset..].chars().next().map_or(0, char::len_utf8);
+        self.span(offset, len)
+    }
+
+    /// Span aus Zeile (1-basiert) und Spalte in Zeichen (0-basiert)
+    pub(crate) fn span_at(&self, line: usize, column: usize) -> Span {
+        let Some(&line_start) = self.line_starts.get(line.saturating_sub(1)) else {
+            return self.span(self.input.len(), 0);
+        };
+        let offset = self.input[line_start..]
+            .char_indices()
+            .nth(column)
+            .map(|(i, _)| line_start + i)
+            .unwrap_or(self.input.len());
+        self.span(offset, 0)
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Kodiert Bytes als Base64 (Standard-Alphabet mit Padding)
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
             } else {
-                Err(FormatError::SerializationError(
-                    "Ungültige Zahl für TOML".to_string()
-                ))
-            }
-        },
-        JsonValue::String(s) => Ok(toml::Value::String(s.clone())),
-        JsonValue::Array(arr) => {
-            // Rekursiv: Jedes Array-Element konvertieren
-            let toml_arr: Result<Vec<toml::Value>, FormatError> = arr
-                .iter()
-                .map(json_to_toml_value)
-                .collect();
-            Ok(toml::Value::Array(toml_arr?))
-        },
-        JsonValue::Object(obj) => {
-            // Rekursiv: Jeden Objekt-Wert konvertieren
-            let mut toml_table = toml::map::Map::new();
-            for (key, value) in obj {
-                toml_table.insert(key.clone(), json_to_toml_value(value)?);
+                out.push('=');
             }
-            Ok(toml::Value::Table(toml_table))
-        },
+        }
     }
+    out
 }
 
+/// Dekodiert Base64 (Whitespace wird ignoriert, z.B. bei YAML `!!binary`)
+pub fn base64_decode(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in input.bytes().filter(|c| !c.is_ascii_whitespace() && *c != b'=') {
+        let value = BASE64_ALPHABET.iter().position(|&a| a == c)? as u32;
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+/// Datetime → (Sekunden seit 1970-01-01 UTC, Nanosekunden)
+///
+/// Ohne Offset wird UTC angenommen, ein reines Datum steht für Mitternacht.
+/// Reine Uhrzeiten haben keinen Zeitpunkt (`None`).
+pub fn datetime_to_epoch(dt: &DateTime) -> Option<(i64, u32)> {
+    let date = dt.date?;
+    let days = days_from_civil(i64::from(date.year), i64::from(date.month), i64::from(date.day));
+    let (seconds, nanos) = match dt.time {
+        Some(t) => (i64::from(t.hour) * 3600 + i64::from(t.minute) * 60 + i64::from(t.second), t.nanosecond),
+        None => (0, 0),
+    };
+    let offset = match dt.offset {
+        Some(toml::value::Offset::Custom { minutes }) => i64::from(minutes) * 60,
+        _ => 0,
+    };
+    Some((days * 86_400 + seconds - offset, nanos))
+}
+
+/// Tage seit 1970-01-01 im proleptischen gregorianischen Kalender
+/// (Algorithmus von Howard Hinnant)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
@@ -0,0 +1,418 @@
+// XML zu anderen Formaten konvertieren
+//
+// Abbildung auf das Zwischenmodell:
+//   <root a="1">          → { "root": { "@a": "1", ... } }   Attribute mit "@"
+//   <name>Text</name>     → "name": "Text"                   reiner Text als String
+//   <name a="1">Text</name> → "name": { "@a": "1", "#text": "Text" }
+//   <item/><item/>        → "item": [ ..., ... ]             Wiederholungen als Array
+//   <name/>               → "name": null
+//   <ns:tag xmlns:ns="u"> → "ns:tag": { "@xmlns:ns": "u" }   Präfixe bleiben erhalten
+//
+// XML kennt keine Typen: Textinhalte bleiben beim Lesen immer Strings.
+// Kommentare, Processing Instructions und DOCTYPE werden ignoriert.
+
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::document::{scalar_to_string, Document, Map, Node, Value};
+use crate::error::FormatError;
+use crate::formats::utils::LineIndex;
+use crate::options::ConvertOptions;
+use crate::registry::{Capabilities, Format};
+use crate::warning::{ConversionWarning, WarningKind};
+
+/// Präfix für Attribute
+pub const ATTRIBUTE_PREFIX: &str = "@";
+/// Key für Textinhalt neben Attributen oder Kindelementen
+pub const TEXT_KEY: &str = "#text";
+/// Root-Element, wenn das Dokument nicht genau ein Element enthält
+const DEFAULT_ROOT: &str = "root";
+/// Elementname für Array-Einträge ohne eigenen Namen
+const ITEM_NAME: &str = "item";
+
+// ============================================================================
+// FORMAT-DEFINITION (für die FormatRegistry)
+// ============================================================================
+
+/// XML als registrierbares Format
+pub struct XmlFormat;
+
+impl Format for XmlFormat {
+    fn name(&self) -> &str {
+        "xml"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["xml"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: true,
+            write: true,
+            nested: true,
+            // Arrays werden in <root><item>...</item></root> verpackt
+            root_array: false,
+            // null wird zum leeren Element
+            null: true,
+            datetime: false,
+            tags: false,
+            aliases: false,
+        }
+    }
+
+    fn read(&self, input: &str) -> Result<Document, FormatError> {
+        read_xml(input)
+    }
+
+    fn write(&self, document: &Document) -> Result<String, FormatError> {
+        write_xml(document)
+    }
+
+    fn write_with_warnings(
+        &self,
+        document: &Document,
+        _options: &ConvertOptions,
+        warnings: &mut Vec<ConversionWarning>,
+    ) -> Result<String, FormatError> {
+        let output = write_xml(document)?;
+        if needs_default_root(document) {
+            warnings.push(ConversionWarning::new(
+                WarningKind::RootWrapped,
+                "",
+                format!("Root in Element <{}> verpackt", DEFAULT_ROOT),
+            ));
+        }
+        Ok(output)
+    }
+}
+
+// ============================================================================
+// READER / WRITER (Zwischenmodell)
+// ============================================================================
+
+/// Offenes Element während des Lesens
+struct Frame {
+    name: String,
+    /// Attribute und Kindelemente
+    map: Map,
+    text: String,
+    offset: usize,
+    len: usize,
+}
+
+/// Liest einen XML String in das Zwischenmodell
+///
+/// Das Ergebnis ist immer eine Map mit genau einem Key: dem Root-Element.
+pub fn read_xml(input: &str) -> Result<Document, FormatError> {
+    let lines = LineIndex::new(input);
+    let mut reader = Reader::from_str(input);
+    reader.config_mut().trim_text(true);
+
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut root: Option<(String, Node)> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| xml_parse_error(input, &lines, e.to_string(), reader.error_position() as usize))?;
+        let position = reader.buffer_position() as usize;
+
+        match event {
+            Event::Start(start) => {
+                // Position zeigt hinter das ">" - zurück zum "<"
+                let len = start.len() + 2;
+                let frame = start_frame(input, &lines, &start, position - len, len)?;
+                if root.is_some() && stack.is_empty() {
+                    return Err(xml_parse_error(input, &lines, "mehr als ein Root-Element".to_string(), position - len));
+                }
+                stack.push(frame);
+            }
+            Event::Empty(start) => {
+                let len = start.len() + 3;
+                let frame = start_frame(input, &lines, &start, position - len, len)?;
+                if root.is_some() && stack.is_empty() {
+                    return Err(xml_parse_error(input, &lines, "mehr als ein Root-Element".to_string(), position - len));
+                }
+                close_frame(frame, &mut stack, &mut root, &lines);
+            }
+            Event::End(_) => {
+                // quick-xml prüft, ob der End-Tag zum Start-Tag passt
+                if let Some(frame) = stack.pop() {
+                    close_frame(frame, &mut stack, &mut root, &lines);
+                }
+            }
+            Event::Text(text) => {
+                let text = text
+                    .unescape()
+                    .map_err(|e| xml_parse_error(input, &lines, e.to_string(), position))?;
+                append_text(&mut stack, &text, input, &lines, position)?;
+            }
+            Event::CData(data) => {
+                let text = String::from_utf8_lossy(&data).into_owned();
+                append_text(&mut stack, &text, input, &lines, position)?;
+            }
+            Event::Eof => break,
+            // Deklaration, Kommentare, PI und DOCTYPE tragen keine Daten
+            _ => {}
+        }
+    }
+
+    if let Some(open) = stack.last() {
+        return Err(xml_parse_error(
+            input,
+            &lines,
+            format!("Element <{}> wird nicht geschlossen", open.name),
+            open.offset,
+        ));
+    }
+
+    let (name, node) = root.ok_or_else(|| FormatError::parse("xml", "kein Root-Element gefunden", input, None))?;
+    let mut map = Map::new();
+    map.push(Node::string(name), node);
+    Ok(Document::new(Node::map(map)))
+}
+
+/// Start-Tag → offenes Element mit Attributen als "@name"
+fn start_frame(
+    input: &str,
+    lines: &LineIndex,
+    start: &BytesStart,
+    offset: usize,
+    len: usize,
+) -> Result<Frame, FormatError> {
+    let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+    let mut map = Map::new();
+
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(|e| xml_parse_error(input, lines, e.to_string(), offset))?;
+        let key = format!("{}{}", ATTRIBUTE_PREFIX, String::from_utf8_lossy(attribute.key.as_ref()));
+        let value = attribute
+            .unescape_value()
+            .map_err(|e| xml_parse_error(input, lines, e.to_string(), offset))?;
+        map.push(Node::string(key), Node::string(value));
+    }
+
+    Ok(Frame { name, map, text: String::new(), offset, len })
+}
+
+/// Text gehört zum innersten offenen Element
+fn append_text(
+    stack: &mut [Frame],
+    text: &str,
+    input: &str,
+    lines: &LineIndex,
+    position: usize,
+) -> Result<(), FormatError> {
+    match stack.last_mut() {
+        Some(frame) => {
+            frame.text.push_str(text);
+            Ok(())
+        }
+        None => Err(xml_parse_error(input, lines, "Text ausserhalb des Root-Elements".to_string(), position)),
+    }
+}
+
+/// Geschlossenes Element in das Eltern-Element einhängen (oder als Root merken)
+fn close_frame(frame: Frame, stack: &mut [Frame], root: &mut Option<(String, Node)>, lines: &LineIndex) {
+    let span = Some(lines.span(frame.offset, frame.len));
+    let mut map = frame.map;
+
+    let node = if map.is_empty() {
+        if frame.text.is_empty() { Node::null() } else { Node::string(frame.text) }
+    } else {
+        if !frame.text.is_empty() {
+            map.push(Node::string(TEXT_KEY), Node::string(frame.text));
+        }
+        Node::map(map)
+    }
+    .with_span(span);
+
+    let Some(parent) = stack.last_mut() else {
+        *root = Some((frame.name, node));
+        return;
+    };
+
+    // Wiederholte Elemente werden zum Array (Elemente selbst sind nie Arrays)
+    match parent.map.get_mut(&frame.name) {
+        Some(existing) => match &mut existing.value {
+            Value::Array(items) => items.push(node),
+            _ => {
+                let first = std::mem::replace(existing, Node::null());
+                *existing = Node::array(vec![first, node]).with_span(span);
+            }
+        },
+        None => parent.map.push(Node::string(frame.name), node),
+    }
+}
+
+/// quick-xml-Fehler → ParseError mit Position und Ausschnitt
+fn xml_parse_error(input: &str, lines: &LineIndex, message: String, offset: usize) -> FormatError {
+    FormatError::parse("xml", message, input, Some(lines.span(offset, 1)))
+}
+
+/// Schreibt das Zwischenmodell als eingerücktes XML
+///
+/// Eine Map mit genau einem Key wird zum Root-Element, alles andere wird in
+/// `<root>` verpackt (Array-Einträge als `<item>`).
+pub fn write_xml(doc: &Document) -> Result<String, FormatError> {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    match &doc.root.value {
+        Value::Map(map) if map.len() == 1 => {
+            let (key, value) = map.iter().next().expect("Map mit einem Eintrag");
+            if matches!(value.value, Value::Array(_)) {
+                // <a/><a/> auf oberster Ebene wäre kein gültiges Dokument
+                write_element(&mut out, DEFAULT_ROOT, &doc.root, 0)?;
+            } else {
+                write_element(&mut out, &key.to_key_string(), value, 0)?;
+            }
+        }
+        Value::Array(items) => {
+            out.push_str(&format!("<{}>\n", DEFAULT_ROOT));
+            for item in items {
+                write_element(&mut out, ITEM_NAME, item, 1)?;
+            }
+            out.push_str(&format!("</{}>\n", DEFAULT_ROOT));
+        }
+        _ => write_element(&mut out, DEFAULT_ROOT, &doc.root, 0)?,
+    }
+
+    Ok(out.trim_end().to_string())
+}
+
+/// Kein einzelnes Root-Element im Dokument - `write_xml` verpackt in <root>
+fn needs_default_root(doc: &Document) -> bool {
+    match &doc.root.value {
+        Value::Map(map) if map.len() == 1 => map.iter().all(|(_, value)| matches!(value.value, Value::Array(_))),
+        _ => true,
+    }
+}
+
+/// Schreibt ein Element samt Inhalt; Arrays werden zu wiederholten Elementen
+fn write_element(out: &mut String, name: &str, node: &Node, depth: usize) -> Result<(), FormatError> {
+    if !is_xml_name(name) {
+        return Err(FormatError::SerializationError(format!("Ungültiger XML-Elementname: {:?}", name)));
+    }
+    let indent = "  ".repeat(depth);
+
+    match &node.value {
+        Value::Array(items) => {
+            for item in items {
+                if let Value::Array(_) = item.value {
+                    // Verschachteltes Array: eigenes Element mit <item>-Einträgen
+                    out.push_str(&format!("{}<{}>\n", indent, name));
+                    write_element(out, ITEM_NAME, item, depth + 1)?;
+                    out.push_str(&format!("{}</{}>\n", indent, name));
+                } else {
+                    write_element(out, name, item, depth)?;
+                }
+            }
+        }
+        Value::Map(map) => {
+            let mut attributes = String::new();
+            let mut text = None;
+            let mut children = Vec::new();
+
+            for (key, value) in map.iter() {
+                let key = key.to_key_string();
+                if key == TEXT_KEY {
+                    text = Some(node_text(value)?);
+                } else if let Some(attribute) = key.strip_prefix(ATTRIBUTE_PREFIX) {
+                    if !is_xml_name(attribute) {
+                        return Err(FormatError::SerializationError(format!(
+                            "Ungültiger XML-Attributname: {:?}",
+                            attribute
+                        )));
+                    }
+                    attributes.push_str(&format!(" {}=\"{}\"", attribute, escape(node_text(value)?)));
+                } else {
+                    children.push((key, value));
+                }
+            }
+
+            match (text, children.is_empty()) {
+                (None, true) => out.push_str(&format!("{}<{}{}/>\n", indent, name, attributes)),
+                (Some(text), true) => {
+                    out.push_str(&format!("{}<{}{}>{}</{}>\n", indent, name, attributes, escape(text), name))
+                }
+                (text, false) => {
+                    out.push_str(&format!("{}<{}{}>\n", indent, name, attributes));
+                    if let Some(text) = text {
+                        out.push_str(&format!("{}  {}\n", indent, escape(text)));
+                    }
+                    for (key, value) in children {
+                        write_element(out, &key, value, depth + 1)?;
+                    }
+                    out.push_str(&format!("{}</{}>\n", indent, name));
+                }
+            }
+        }
+        Value::Null => out.push_str(&format!("{}<{}/>\n", indent, name)),
+        _ => {
+            let text = node_text(node)?;
+            out.push_str(&format!("{}<{}>{}</{}>\n", indent, name, escape(text), name));
+        }
+    }
+    Ok(())
+}
+
+/// Skalar als Text für Attribute und Textinhalt
+fn node_text(node: &Node) -> Result<String, FormatError> {
+    match &node.value {
+        Value::Null => Ok(String::new()),
+        value => scalar_to_string(value).ok_or_else(|| {
+            FormatError::SerializationError(format!(
+                "{} kann nicht als XML-Text oder Attribut geschrieben werden",
+                value.type_name()
+            ))
+        }),
+    }
+}
+
+/// Vereinfachte Prüfung nach XML-Spezifikation (Namespace-Präfixe erlaubt)
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_mapping() {
+        let xml = r#"<?xml version="1.0"?>
+<catalog xmlns:dc="http://purl.org/dc">
+  <book id="1"><dc:title>Rust</dc:title></book>
+  <book id="2" lang="de">Buch &amp; mehr</book>
+  <empty/>
+</catalog>"#;
+        let doc = read_xml(xml).unwrap();
+        let catalog = doc.root.as_map().unwrap().get("catalog").unwrap().as_map().unwrap();
+
+        assert_eq!(catalog.get("@xmlns:dc").and_then(Node::as_str), Some("http://purl.org/dc"));
+        let books = catalog.get("book").unwrap().as_array().unwrap();
+        assert_eq!(books.len(), 2);
+        assert_eq!(books[0].as_map().unwrap().get("dc:title").and_then(Node::as_str), Some("Rust"));
+        assert_eq!(books[1].as_map().unwrap().get("#text").and_then(Node::as_str), Some("Buch & mehr"));
+        assert!(catalog.get("empty").unwrap().is_null());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<config version=\"2\">\n  <name>a &lt; b</name>\n  <port>80</port>\n  <port>443</port>\n  <debug/>\n</config>";
+        assert_eq!(write_xml(&read_xml(xml).unwrap()).unwrap(), xml);
+    }
+
+    #[test]
+    fn test_parse_error_has_location() {
+        let err = read_xml("<a>\n  <b></c>\n</a>").unwrap_err();
+        assert_eq!(err.span().map(|s| s.line), Some(2));
+    }
+}
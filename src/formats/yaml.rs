@@ -1,8 +1,1143 @@
 // YAML zu anderen Formaten konvertieren
 
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::path::Path;
+use std::io::Write;
+use crate::document::{parse_number, Anchor, Comment, DateTime, Document, Map, Node, Span, Value};
 use crate::error::FormatError;
-use crate::formats::utils::json_to_toml_value;
+use crate::options::ConvertOptions;
+use crate::registry::{Capabilities, Format};
+use crate::stream::{write_error, RecordWriter};
+use crate::formats::csv::write_csv;
+use crate::formats::json::write_json;
+use crate::formats::toml::write_toml;
+use crate::formats::utils::{base64_decode, LineIndex};
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser, Tag};
+use yaml_rust2::scanner::{Marker, Scanner, TScalarStyle, TokenType};
+
+/// Präfix der Standard-Tags (`!!str`, `!!int`, ...) nach der Auflösung durch den Parser
+const YAML_CORE_TAG_HANDLE: &str = "tag:yaml.org,2002:";
+
+/// Standard-Limit für Knoten, die durch Aliase entstehen dürfen
+pub const DEFAULT_MAX_ALIAS_NODES: usize = 1_000_000;
+
+// ============================================================================
+// FORMAT-DEFINITION (für die FormatRegistry)
+// ============================================================================
+
+/// YAML als registrierbares Format
+pub struct YamlFormat;
+
+impl Format for YamlFormat {
+    fn name(&self) -> &str {
+        "yaml"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["yml"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["yaml", "yml"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: true,
+            write: true,
+            nested: true,
+            root_array: true,
+            null: true,
+            datetime: true,
+            tags: true,
+            aliases: true,
+        }
+    }
+
+    fn read(&self, input: &str) -> Result<Document, FormatError> {
+        read_yaml(input)
+    }
+
+    fn read_with(&self, input: &str, options: &ConvertOptions) -> Result<Document, FormatError> {
+        read_yaml_with(input, &options.yaml)
+    }
+
+    fn write(&self, document: &Document) -> Result<String, FormatError> {
+        write_yaml(document)
+    }
+
+    fn write_with(&self, document: &Document, options: &ConvertOptions) -> Result<String, FormatError> {
+        write_yaml_with(document, &options.yaml)
+    }
+
+    fn record_writer<'a>(
+        &self,
+        output: &'a mut dyn Write,
+        options: &ConvertOptions,
+    ) -> Option<Box<dyn RecordWriter + 'a>> {
+        Some(Box::new(YamlRecordWriter { output, options: options.yaml.clone(), count: 0 }))
+    }
+}
+
+/// Einstellungen für YAML-Eingabe und -Ausgabe
+#[derive(Debug, Clone, PartialEq)]
+pub struct YamlOptions {
+    /// Ein Root-Array als Stream mehrerer Dokumente (`---`) schreiben,
+    /// z.B. für Kubernetes-Manifeste
+    pub multi_document: bool,
+    /// Merge-Keys (`<<: *defaults`) nicht auflösen, sondern als normale Keys
+    /// behalten, damit sie beim Schreiben als YAML erhalten bleiben (Anker
+    /// und Aliase schreibt YAML immer wieder). Gilt in `convert_reporting`
+    /// nur, wenn das Ziel Aliase kennt (`Capabilities::aliases`).
+    pub keep_aliases: bool,
+    /// Maximale Anzahl Knoten, die durch Aliase kopiert werden
+    /// (Schutz vor "Billion Laughs")
+    pub max_alias_nodes: usize,
+}
+
+impl Default for YamlOptions {
+    fn default() -> Self {
+        YamlOptions { multi_document: false, keep_aliases: false, max_alias_nodes: DEFAULT_MAX_ALIAS_NODES }
+    }
+}
+
+// ============================================================================
+// READER / WRITER (Zwischenmodell)
+// ============================================================================
+
+/// Liest einen YAML String in das Zwischenmodell
+///
+/// Plain Scalars werden nach dem YAML 1.2 Core Schema typisiert, dazu
+/// Zeitstempel wie `2024-01-15T10:30:00Z` oder `2024-01-15` als Datetime;
+/// eigene Tags (z.B. `!Ref`) bleiben am Knoten erhalten. Kommentare über
+/// und hinter einem Key werden an den Key gehängt (siehe `Comment`).
+///
+/// Enthält die Eingabe mehrere Dokumente (`---`), wird die Root ein Array
+/// mit einem Eintrag pro Dokument. Aliase werden kopiert und Merge-Keys
+/// (`<<`) nach der YAML 1.1 Merge-Spezifikation aufgelöst.
+pub fn read_yaml(input: &str) -> Result<Document, FormatError> {
+    read_yaml_with(input, &YamlOptions::default())
+}
+
+/// Wie `read_yaml`, mit Limit für Aliase und ggf. ohne Auflösen der Merge-Keys
+pub fn read_yaml_with(input: &str, options: &YamlOptions) -> Result<Document, FormatError> {
+    let mut loader = YamlLoader::new(input, options);
+    let mut parser = Parser::new_from_str(input);
+
+    parser.load(&mut loader, true).map_err(|e| {
+        let span = loader.lines.span_at(e.marker().line(), e.marker().col());
+        FormatError::parse("yaml", e.info(), input, Some(span))
+    })?;
+
+    if let Some((message, span)) = loader.error {
+        return Err(FormatError::parse("yaml", message, input, span));
+    }
+
+    let lines = scan_lines(input);
+    let mut claimed = HashSet::new();
+    for document in &mut loader.documents {
+        attach_comments(document, &lines, &mut claimed);
+    }
+
+    let root = match loader.documents.len() {
+        0 => Node::null(),
+        1 => loader.documents.remove(0),
+        _ => Node::array(loader.documents).with_span(loader.document_starts.first().copied()),
+    };
+    Ok(Document::new(root))
+}
+
+/// Schreibt das Zwischenmodell als YAML String
+///
+/// Kommentare und Leerzeilen, die ein Reader an Map-Keys und Array-Einträge
+/// gehängt hat, werden wieder über bzw. hinter den Eintrag geschrieben,
+/// YAML-Anker und -Aliase wieder als `&name` / `*name`.
+pub fn write_yaml(doc: &Document) -> Result<String, FormatError> {
+    write_yaml_with(doc, &YamlOptions::default())
+}
+
+/// Wie `write_yaml`; mit `multi_document` wird ein Root-Array zu einem
+/// Stream mit einem Dokument pro Eintrag
+///
+/// # Beispiel
+/// ```
+/// use asp_cli::formats::json::read_json;
+/// use asp_cli::formats::yaml::{write_yaml_with, YamlOptions};
+///
+/// let doc = read_json(r#"[{"kind": "Service"}, {"kind": "Deployment"}]"#)?;
+/// let options = YamlOptions { multi_document: true, ..YamlOptions::default() };
+/// let yaml = write_yaml_with(&doc, &options)?;
+/// assert_eq!(yaml, "kind: Service\n---\nkind: Deployment\n");
+/// # Ok::<(), asp_cli::FormatError>(())
+/// ```
+pub fn write_yaml_with(doc: &Document, options: &YamlOptions) -> Result<String, FormatError> {
+    let write = |node: &Node| YamlEmitter::default().document(node);
+
+    match &doc.root.value {
+        Value::Array(items) if options.multi_document => Ok(items.iter().map(write).collect::<Vec<_>>().join("---\n")),
+        _ => Ok(write(&doc.root)),
+    }
+}
+
+/// Schreibt ein Root-Array Eintrag für Eintrag, Ausgabe wie `write_yaml_with`
+///
+/// Jeder Eintrag wird als Array mit einem Element geschrieben (`- ...`),
+/// mit `multi_document` als eigenes Dokument.
+struct YamlRecordWriter<'a> {
+    output: &'a mut dyn Write,
+    options: YamlOptions,
+    count: usize,
+}
+
+impl RecordWriter for YamlRecordWriter<'_> {
+    fn write_record(&mut self, record: &Node) -> Result<(), FormatError> {
+        let (separator, document) = if self.options.multi_document {
+            (if self.count == 0 { "" } else { "---\n" }, Document::new(record.clone()))
+        } else {
+            ("", Document::new(Node::array(vec![record.clone()])))
+        };
+        self.count += 1;
+        let text = write_yaml_with(&document, &YamlOptions { multi_document: false, ..self.options.clone() })?;
+        write!(self.output, "{}{}", separator, text).map_err(write_error)
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), FormatError> {
+        if self.count == 0 {
+            let empty = write_yaml_with(&Document::new(Node::array(Vec::new())), &self.options)?;
+            self.output.write_all(empty.as_bytes()).map_err(write_error)?;
+        }
+        self.output.flush().map_err(write_error)
+    }
+}
+
+// ============================================================================
+// KOMMENTARE
+// ============================================================================
+
+/// Art einer Zeile (Kommentartext ohne `#`)
+enum Line {
+    /// Inhalt, auch Zeilen in Block-Scalars
+    Content,
+    /// Leerzeile ausserhalb von Block-Scalars
+    Blank,
+    /// Zeile besteht nur aus dem Kommentar
+    Comment(String),
+    /// Kommentar hinter Inhalt
+    Trailing(String),
+}
+
+/// Ordnet jede Zeile ein (Index = Zeile - 1)
+///
+/// Der Parser liefert keine Kommentare, daher ein eigener Scan: `#` zählt
+/// nur am Zeilenanfang oder nach Leerraum und ausserhalb von Quotes. Zeilen
+/// in Block-Scalars (`|`, `>`) sind Inhalt, Leerzeilen an ihrem Ende nur
+/// mit `+` (sonst gehören sie nicht zum Wert).
+fn scan_lines(input: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    // Einrückung der Zeile, die einen Block-Scalar einleitet, und ob er
+    // Leerzeilen am Ende behält
+    let mut block: Option<(usize, bool)> = None;
+    let mut block_blanks = 0;
+
+    for line in input.lines() {
+        let indent = line.len() - line.trim_start().len();
+        if let Some((block_indent, keep)) = block {
+            if line.trim().is_empty() || indent > block_indent {
+                block_blanks = if line.trim().is_empty() { block_blanks + 1 } else { 0 };
+                lines.push(Line::Content);
+                continue;
+            }
+            if !keep {
+                let start = lines.len() - block_blanks;
+                lines[start..].iter_mut().for_each(|line| *line = Line::Blank);
+            }
+            block = None;
+        }
+
+        if line.trim().is_empty() {
+            lines.push(Line::Blank);
+            continue;
+        }
+
+        let comment_start = find_comment(line);
+        let content = comment_start.map_or(line, |start| &line[..start]).trim_end();
+        if let Some(keep) = block_scalar_header(content) {
+            block = Some((indent, keep));
+            block_blanks = 0;
+        }
+
+        lines.push(match comment_start {
+            Some(start) => {
+                let text = line[start + 1..].trim_end().to_string();
+                if content.is_empty() { Line::Comment(text) } else { Line::Trailing(text) }
+            }
+            None => Line::Content,
+        });
+    }
+    lines
+}
+
+/// Byte-Position des `#`, das einen Kommentar beginnt
+fn find_comment(line: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '#') if previous.is_whitespace() => return Some(i),
+            (None, '"' | '\'') if previous.is_whitespace() || matches!(previous, ':' | '[' | '{' | ',' | '-') => {
+                quote = Some(c)
+            }
+            (Some('"'), '"') if previous != '\\' => quote = None,
+            (Some('\''), '\'') => quote = None,
+            _ => {}
+        }
+        previous = c;
+    }
+    None
+}
+
+/// `key: |`, `- >-`, `key: |2+` usw.; `Some(true)` mit `+` (Leerzeilen am
+/// Ende gehören zum Wert)
+fn block_scalar_header(content: &str) -> Option<bool> {
+    let last = content.rsplit([' ', '\t']).next().unwrap_or_default();
+    let mut chars = last.chars();
+    let is_header = matches!(chars.next(), Some('|' | '>'))
+        && chars.all(|c| matches!(c, '+' | '-' | '1'..='9'))
+        && content.len() > last.len();
+    is_header.then(|| last.contains('+'))
+}
+
+/// Hängt Kommentare und Leerzeilen an Map-Keys und Array-Einträge
+///
+/// Jede Zeile gehört zum ersten Knoten, der auf ihr beginnt: bei `- name: a`
+/// also zum Key `name`, bei `[1, 2] # Kommentar` zur `1`. Kopien durch
+/// Aliase bekommen keine Kommentare.
+fn attach_comments(node: &mut Node, lines: &[Line], claimed: &mut HashSet<usize>) {
+    match &mut node.value {
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                let is_alias = matches!(item.anchor.as_deref(), Some(Anchor::Alias(_)));
+                let nested = match &item.value {
+                    Value::Array(items) => !items.is_empty(),
+                    Value::Map(map) => !map.is_empty(),
+                    _ => false,
+                };
+                if !nested || is_alias {
+                    claim_comment(item, lines, claimed);
+                }
+                if !is_alias {
+                    attach_comments(item, lines, claimed);
+                }
+            }
+        }
+        Value::Map(map) => {
+            for (key, value) in map.iter_mut() {
+                claim_comment(key, lines, claimed);
+                if !matches!(value.anchor.as_deref(), Some(Anchor::Alias(_))) {
+                    attach_comments(value, lines, claimed);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn claim_comment(node: &mut Node, lines: &[Line], claimed: &mut HashSet<usize>) {
+    if let Some(span) = node.span
+        && claimed.insert(span.line)
+    {
+        node.comment = comment_for_line(lines, span.line).map(Box::new);
+    }
+}
+
+/// Kommentarzeilen und Leerzeilen darüber plus Kommentar am Zeilenende
+///
+/// Leerzeilen zwischen zwei Kommentarzeilen entfallen.
+fn comment_for_line(lines: &[Line], line: usize) -> Option<Comment> {
+    let index = line.checked_sub(1)?;
+    let mut comment = Comment {
+        trailing: match lines.get(index) {
+            Some(Line::Trailing(text)) => Some(text.clone()),
+            _ => None,
+        },
+        ..Comment::default()
+    };
+
+    for line in lines[..index.min(lines.len())].iter().rev() {
+        match line {
+            Line::Blank if comment.before.is_empty() => comment.blank_lines_after += 1,
+            Line::Blank => comment.blank_lines += 1,
+            Line::Comment(text) => {
+                comment.blank_lines = 0;
+                comment.before.push(text.clone());
+            }
+            Line::Content | Line::Trailing(_) => break,
+        }
+    }
+    comment.before.reverse();
+    if comment.before.is_empty() {
+        comment.blank_lines = std::mem::take(&mut comment.blank_lines_after);
+    }
+
+    (comment != Comment::default()).then_some(comment)
+}
+
+/// Baut aus den Parser-Events die Knoten auf
+struct YamlLoader<'a> {
+    lines: LineIndex<'a>,
+    documents: Vec<Node>,
+    /// Position jedes `---` bzw. Dokumentanfangs
+    document_starts: Vec<Span>,
+    stack: Vec<OpenNode>,
+    /// Verankerte Knoten mit ihrer Knotenanzahl
+    anchors: HashMap<usize, (Node, usize)>,
+    /// Anker-Namen; der Parser vergibt die IDs fortlaufend ab 1
+    anchor_names: Vec<String>,
+    /// Merge-Keys als normale Keys behalten statt aufzulösen
+    keep_merge_keys: bool,
+    /// Bisher durch Aliase kopierte Knoten und das Limit dafür
+    alias_nodes: usize,
+    max_alias_nodes: usize,
+    /// Erster Fehler (Meldung, Position) - Events können nicht abbrechen
+    error: Option<(String, Option<Span>)>,
+}
+
+/// Offene Sequenz bzw. offenes Mapping
+struct OpenNode {
+    node: Node,
+    anchor_id: usize,
+    /// Key, dessen Wert noch fehlt
+    pending_key: Option<Node>,
+    /// Der nächste Wert gehört zu einem Merge-Key `<<`
+    pending_merge: bool,
+    /// Merge-Werte mit der Position, an der sie im Mapping standen
+    merges: Vec<(usize, Node)>,
+}
+
+impl<'a> YamlLoader<'a> {
+    fn new(input: &'a str, options: &YamlOptions) -> Self {
+        YamlLoader {
+            lines: LineIndex::new(input),
+            documents: Vec::new(),
+            document_starts: Vec::new(),
+            stack: Vec::new(),
+            anchors: HashMap::new(),
+            anchor_names: anchor_names(input),
+            keep_merge_keys: options.keep_aliases,
+            alias_nodes: 0,
+            max_alias_nodes: options.max_alias_nodes,
+            error: None,
+        }
+    }
+
+    fn open(&mut self, node: Node, anchor_id: usize) {
+        self.stack.push(OpenNode { node, anchor_id, pending_key: None, pending_merge: false, merges: Vec::new() });
+    }
+
+    fn fail(&mut self, message: String, span: Option<Span>) {
+        if self.error.is_none() {
+            self.error = Some((message, span));
+        }
+    }
+
+    fn anchor_name(&self, anchor_id: usize) -> String {
+        self.anchor_names.get(anchor_id - 1).cloned().unwrap_or_else(|| format!("anchor{}", anchor_id))
+    }
+
+    /// Ist der Scalar ein Merge-Key an Key-Position eines Mappings?
+    fn is_merge_key(&self, text: &str, style: TScalarStyle, tag: Option<&Tag>) -> bool {
+        !self.keep_merge_keys
+            && text == "<<"
+            && style == TScalarStyle::Plain
+            && tag.is_none()
+            && self
+                .stack
+                .last()
+                .is_some_and(|open| matches!(open.node.value, Value::Map(_)) && open.pending_key.is_none())
+    }
+
+    /// Fügt einen fertigen Knoten in den aktuellen Container ein
+    fn push_node(&mut self, mut node: Node, anchor_id: usize) {
+        if anchor_id > 0 {
+            node = node.with_anchor(Some(Anchor::Define(self.anchor_name(anchor_id))));
+            self.anchors.insert(anchor_id, (node.clone(), count_nodes(&node)));
+        }
+
+        let Some(open) = self.stack.last_mut() else {
+            self.documents.push(node);
+            return;
+        };
+
+        match &mut open.node.value {
+            Value::Array(items) => items.push(node),
+            Value::Map(map) if open.pending_merge => {
+                open.pending_merge = false;
+                open.merges.push((map.len(), node));
+            }
+            Value::Map(map) => match open.pending_key.take() {
+                None => open.pending_key = Some(node),
+                Some(key) => {
+                    if map.contains_key(&key) && self.error.is_none() {
+                        self.error = Some((format!("doppelter Schlüssel '{}'", key.to_key_string()), key.span));
+                    }
+                    map.insert(key, node);
+                }
+            },
+            _ => unreachable!("nur Sequenzen und Mappings liegen auf dem Stack"),
+        }
+    }
+}
+
+impl MarkedEventReceiver for YamlLoader<'_> {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        let span = Some(self.lines.span_at(mark.line(), mark.col()));
+
+        match event {
+            Event::Scalar(text, style, anchor_id, tag) => {
+                if self.is_merge_key(&text, style, tag.as_ref()) {
+                    if let Some(open) = self.stack.last_mut() {
+                        open.pending_merge = true;
+                    }
+                    return;
+                }
+                match resolve_scalar(text, style, tag.as_ref()) {
+                    Ok(node) => self.push_node(node.with_span(span), anchor_id),
+                    Err(message) => {
+                        self.fail(message, span);
+                        self.push_node(Node::null(), anchor_id);
+                    }
+                }
+            }
+            Event::SequenceStart(anchor_id, tag) => {
+                let node = Node::array(Vec::new()).with_span(span).with_tag(custom_tag(tag.as_ref()));
+                self.open(node, anchor_id);
+            }
+            Event::MappingStart(anchor_id, tag) => {
+                let node = Node::map(Map::new()).with_span(span).with_tag(custom_tag(tag.as_ref()));
+                self.open(node, anchor_id);
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                if let Some(OpenNode { mut node, anchor_id, merges, .. }) = self.stack.pop() {
+                    if !merges.is_empty()
+                        && let Value::Map(map) = std::mem::replace(&mut node.value, Value::Null)
+                    {
+                        match apply_merges(map, merges) {
+                            Ok(map) => node.value = Value::Map(map),
+                            Err(message) => self.fail(message, node.span),
+                        }
+                    }
+                    self.push_node(node, anchor_id);
+                }
+            }
+            Event::Alias(anchor_id) => {
+                // Aliase werden aufgelöst, indem der verankerte Knoten kopiert wird
+                let node = match self.anchors.get(&anchor_id) {
+                    Some((_, count)) if self.alias_nodes + count > self.max_alias_nodes => {
+                        let message = format!(
+                            "Aliase erzeugen mehr als {} Knoten (Schutz vor Alias-Bomben)",
+                            self.max_alias_nodes
+                        );
+                        self.fail(message, span);
+                        Node::null()
+                    }
+                    Some((node, count)) => {
+                        self.alias_nodes += count;
+                        let name = self.anchor_name(anchor_id);
+                        node.clone().with_span(span).with_anchor(Some(Anchor::Alias(name)))
+                    }
+                    None => Node::null(),
+                };
+                self.push_node(node, 0);
+            }
+            Event::DocumentStart => {
+                if let Some(span) = span {
+                    self.document_starts.push(span);
+                }
+            }
+            Event::Nothing | Event::StreamStart | Event::StreamEnd | Event::DocumentEnd => {}
+        }
+    }
+}
+
+/// Namen aller Anker in der Reihenfolge ihres Auftretens
+fn anchor_names(input: &str) -> Vec<String> {
+    if !input.contains('&') {
+        return Vec::new();
+    }
+    Scanner::new(input.chars())
+        .map_while(|token| match token.1 {
+            TokenType::Anchor(name) => Some(Some(name)),
+            TokenType::StreamEnd => None,
+            _ => Some(None),
+        })
+        .flatten()
+        .collect()
+}
+
+/// Anzahl Knoten im Teilbaum (Keys mitgezählt)
+fn count_nodes(node: &Node) -> usize {
+    1 + match &node.value {
+        Value::Array(items) => items.iter().map(count_nodes).sum(),
+        Value::Map(map) => map.iter().map(|(k, v)| count_nodes(k) + count_nodes(v)).sum(),
+        _ => 0,
+    }
+}
+
+/// Löst Merge-Keys nach <https://yaml.org/type/merge.html> auf
+///
+/// Eigene Keys des Mappings haben Vorrang vor gemergten, bei einer Liste
+/// von Mappings gewinnt das erste. Gemergte Keys stehen dort, wo `<<` stand.
+fn apply_merges(map: Map, merges: Vec<(usize, Node)>) -> Result<Map, String> {
+    let mut sources: Vec<(usize, Map)> = Vec::new();
+    for (position, value) in merges {
+        match value.value {
+            Value::Map(source) => sources.push((position, source)),
+            Value::Array(items) => {
+                for item in items {
+                    match item.value {
+                        Value::Map(source) => sources.push((position, source)),
+                        other => return Err(merge_type_error(&other)),
+                    }
+                }
+            }
+            other => return Err(merge_type_error(&other)),
+        }
+    }
+
+    let own: Map = map.iter().map(|(key, _)| (key.clone(), Node::null())).collect();
+    let mut merged = Map::new();
+    let mut sources = sources.into_iter().peekable();
+    for (index, (key, value)) in map.into_iter().enumerate() {
+        while let Some((_, source)) = sources.next_if(|(position, _)| *position == index) {
+            merge_into(&mut merged, source, &own);
+        }
+        merged.push(key, value);
+    }
+    for (_, source) in sources {
+        merge_into(&mut merged, source, &own);
+    }
+    Ok(merged)
+}
+
+fn merge_into(target: &mut Map, source: Map, own: &Map) {
+    for (key, value) in source {
+        if !own.contains_key(&key) && !target.contains_key(&key) {
+            target.push(key, value);
+        }
+    }
+}
+
+fn merge_type_error(value: &Value) -> String {
+    format!("Merge-Key << erwartet ein Mapping oder eine Liste von Mappings, gefunden: {}", value.type_name())
+}
+
+/// Tag als String - Standard-Tags werden zu `!!name` abgekürzt
+fn tag_to_string(tag: &Tag) -> String {
+    if tag.handle == YAML_CORE_TAG_HANDLE {
+        format!("!!{}", tag.suffix)
+    } else if tag.handle.is_empty() {
+        format!("!<{}>", tag.suffix)
+    } else {
+        format!("{}{}", tag.handle, tag.suffix)
+    }
+}
+
+/// Eigene Tags werden am Knoten gespeichert, Standard-Tags nicht
+/// (sie bestimmen nur den Typ des Wertes)
+fn custom_tag(tag: Option<&Tag>) -> Option<String> {
+    tag.filter(|t| t.handle != YAML_CORE_TAG_HANDLE).map(tag_to_string)
+}
+
+/// Typisiert einen Scalar anhand von Tag, Stil und Inhalt
+fn resolve_scalar(text: String, style: TScalarStyle, tag: Option<&Tag>) -> Result<Node, String> {
+    if let Some(tag) = tag.filter(|t| t.handle == YAML_CORE_TAG_HANDLE) {
+        let value = match tag.suffix.as_str() {
+            "str" => Value::String(text),
+            "null" => Value::Null,
+            "bool" => match resolve_plain(&text) {
+                Value::Bool(b) => Value::Bool(b),
+                _ => return Err(format!("'{}' ist kein gültiger !!bool", text)),
+            },
+            "int" => match resolve_plain(&text) {
+                v @ (Value::Integer(_) | Value::BigInt(_)) => v,
+                _ => return Err(format!("'{}' ist kein gültiger !!int", text)),
+            },
+            "float" => match resolve_plain(&text) {
+                Value::Integer(i) => Value::Float(i as f64),
+                Value::BigInt(s) => Value::Decimal(s),
+                v @ (Value::Float(_) | Value::Decimal(_)) => v,
+                _ => return Err(format!("'{}' ist kein gültiger !!float", text)),
+            },
+            "binary" => match base64_decode(&text) {
+                Some(bytes) => Value::Bytes(bytes),
+                None => return Err("ungültiges Base64 in !!binary".to_string()),
+            },
+            "timestamp" => match text.parse().ok() {
+                Some(dt) => Value::DateTime(dt),
+                None => return Err(format!("'{}' ist kein gültiger !!timestamp", text)),
+            },
+            // Unbekannte Standard-Tags (z.B. !!set, !!omap) behalten den Tag
+            _ => return Ok(Node::string(text).with_tag(Some(tag_to_string(tag)))),
+        };
+        return Ok(Node::new(value));
+    }
+
+    let value = if style == TScalarStyle::Plain {
+        resolve_plain(&text)
+    } else {
+        Value::String(text)
+    };
+    Ok(Node::new(value).with_tag(custom_tag(tag)))
+}
+
+/// YAML 1.2 Core Schema für Plain Scalars, plus Zeitstempel aus YAML 1.1
+fn resolve_plain(text: &str) -> Value {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => return Value::Null,
+        "true" | "True" | "TRUE" => return Value::Bool(true),
+        "false" | "False" | "FALSE" => return Value::Bool(false),
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => return Value::Float(f64::INFINITY),
+        "-.inf" | "-.Inf" | "-.INF" => return Value::Float(f64::NEG_INFINITY),
+        ".nan" | ".NaN" | ".NAN" => return Value::Float(f64::NAN),
+        _ => {}
+    }
+
+    if let Some(dt) = parse_timestamp(text) {
+        return Value::DateTime(dt);
+    }
+
+    if let Some(hex) = text.strip_prefix("0x") {
+        if let Ok(i) = i64::from_str_radix(hex, 16) {
+            return Value::Integer(i);
+        }
+    } else if let Some(oct) = text.strip_prefix("0o") {
+        if let Ok(i) = i64::from_str_radix(oct, 8) {
+            return Value::Integer(i);
+        }
+    } else if is_yaml_int(text) {
+        return match text.parse::<i64>() {
+            Ok(i) => Value::Integer(i),
+            Err(_) => Value::BigInt(text.trim_start_matches('+').to_string()),
+        };
+    } else if is_yaml_float(text)
+        && let Some(number) = parse_number(text)
+    {
+        // Viele Stellen oder ausserhalb von f64 → Decimal
+        return number;
+    }
+
+    Value::String(text.to_string())
+}
+
+/// Zeitstempel nach RFC 3339 bzw. TOML: `2024-01-15`, `2024-01-15T10:30:00Z`,
+/// `2024-01-15 10:30:00.5+02:00`, ... (immer mit Datum)
+fn parse_timestamp(text: &str) -> Option<DateTime> {
+    let bytes = text.as_bytes();
+    let has_date = bytes.len() >= 10 && bytes[..4].iter().all(u8::is_ascii_digit) && bytes[4] == b'-';
+    if has_date { text.parse().ok() } else { None }
+}
+
+/// `[-+]?[0-9]+`
+fn is_yaml_int(text: &str) -> bool {
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// `[-+]?(\.[0-9]+|[0-9]+(\.[0-9]*)?)([eE][-+]?[0-9]+)?`
+fn is_yaml_float(text: &str) -> bool {
+    let body = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let (mantissa, exponent) = match body.split_once(['e', 'E']) {
+        Some((m, e)) => (m, Some(e)),
+        None => (body, None),
+    };
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let mantissa_ok = (!int_part.is_empty() || !frac_part.is_empty())
+        && int_part.bytes().all(|b| b.is_ascii_digit())
+        && frac_part.bytes().all(|b| b.is_ascii_digit());
+    let exponent_ok = exponent.is_none_or(is_yaml_int);
+    mantissa_ok && exponent_ok
+}
+
+/// Node → serde_yaml::Value (eigene Tags bleiben erhalten)
+fn node_to_yaml_value(node: &Node) -> serde_yaml::Value {
+    let value = match &node.value {
+        Value::Null => serde_yaml::Value::Null,
+        Value::Bool(b) => serde_yaml::Value::Bool(*b),
+        Value::Integer(i) => serde_yaml::Value::Number((*i).into()),
+        Value::Float(f) => serde_yaml::Value::Number((*f).into()),
+        Value::String(s) => serde_yaml::Value::String(s.clone()),
+        Value::Array(items) => serde_yaml::Value::Sequence(items.iter().map(node_to_yaml_value).collect()),
+        Value::Map(map) => serde_yaml::Value::Mapping(
+            map.iter().map(|(key, value)| (node_to_yaml_value(key), node_to_yaml_value(value))).collect(),
+        ),
+        // serde_yaml kennt nur i64/u64/f64 - grössere Zahlen als Text
+        Value::BigInt(_) | Value::Decimal(_) | Value::Bytes(_) | Value::DateTime(_) => {
+            serde_yaml::Value::String(crate::document::scalar_to_string(&node.value).unwrap_or_default())
+        }
+    };
+    match &node.tag {
+        Some(tag) => serde_yaml::Value::Tagged(Box::new(serde_yaml::value::TaggedValue {
+            tag: serde_yaml::value::Tag::new(tag),
+            value,
+        })),
+        None => value,
+    }
+}
+
+// ============================================================================
+// YAML-AUSGABE
+// ============================================================================
+
+/// Schreibt Knoten als YAML im Block-Stil
+///
+/// Kommentare und Leerzeilen kommen von Map-Keys und Array-Einträgen, Anker
+/// und Aliase von den Knoten selbst. Ein Alias wird nur geschrieben, wenn
+/// sein Anker schon vorkam und der Knoten noch gleich ist wie der verankerte
+/// (nach `--sort-keys` oder `edit` sonst ungültig bzw. falsch), sonst steht
+/// dort eine Kopie.
+#[derive(Default)]
+struct YamlEmitter<'a> {
+    out: String,
+    /// Bereits geschriebene Anker
+    anchors: HashMap<&'a str, &'a Node>,
+    /// Einrückung der aktuellen Zeile, solange ihr Anfang fehlt: bei
+    /// `- - x` und `- key: x` folgen die `- ` erst mit dem ersten Eintrag,
+    /// damit dessen Kommentare über der Zeile stehen können
+    pending_line: Option<usize>,
+    /// Zuletzt kam ein Block-Scalar mit `|+` - Leerzeilen gehörten zu ihm
+    keeps_blank_lines: bool,
+}
+
+/// Wo ein Wert steht
+#[derive(Clone, Copy, PartialEq)]
+enum Parent {
+    Document,
+    Map,
+    Sequence,
+}
+
+impl<'a> YamlEmitter<'a> {
+    /// Ein Knoten als YAML-Dokument
+    fn document(mut self, node: &'a Node) -> String {
+        let comment = node.comment.as_deref();
+        self.comment_lines(0, comment);
+        self.value(node, 0, comment.and_then(|c| c.trailing.as_deref()), Parent::Document);
+        self.out
+    }
+
+    fn map(&mut self, map: &'a Map, indent: usize) {
+        for (key, value) in map.iter() {
+            let comment = key.comment.as_deref();
+            self.line_start(indent, comment);
+            self.key(key);
+            self.out.push(':');
+            self.value(value, indent, comment.and_then(|c| c.trailing.as_deref()), Parent::Map);
+        }
+    }
+
+    fn sequence(&mut self, items: &'a [Node], indent: usize) {
+        for item in items {
+            let comment = item.comment.as_deref();
+            let trailing = comment.and_then(|c| c.trailing.as_deref());
+
+            // Verschachtelte Collections beginnen auf der Zeile des `- `
+            match &item.value {
+                Value::Map(map) if trailing.is_none() && self.is_compact(item) => {
+                    self.compact_start(indent, comment);
+                    self.map(map, indent + 2);
+                }
+                Value::Array(items) if trailing.is_none() && self.is_compact(item) => {
+                    self.compact_start(indent, comment);
+                    self.sequence(items, indent + 2);
+                }
+                _ => {
+                    self.line_start(indent, comment);
+                    self.out.push('-');
+                    self.value(item, indent, trailing, Parent::Sequence);
+                }
+            }
+        }
+    }
+
+    /// Wert hinter `key:`, `-` bzw. am Dokumentanfang; `indent` ist die
+    /// Einrückung des Keys bzw. des `-`
+    fn value(&mut self, node: &'a Node, indent: usize, trailing: Option<&str>, parent: Parent) {
+        if let Some(name) = self.alias(node) {
+            self.word(&format!("*{}", name));
+            self.trailing(trailing);
+            self.out.push('\n');
+            return;
+        }
+
+        self.properties(node);
+        match &node.value {
+            Value::Map(map) if !map.is_empty() => {
+                self.trailing(trailing);
+                self.end_line();
+                self.map(map, if parent == Parent::Document { 0 } else { indent + 2 });
+            }
+            // Wie serde_yaml: Sequenzen in Maps nicht zusätzlich eingerückt
+            Value::Array(items) if !items.is_empty() => {
+                self.trailing(trailing);
+                self.end_line();
+                self.sequence(items, if parent == Parent::Sequence { indent + 2 } else { indent });
+            }
+            Value::String(s) if is_literal(s) => {
+                let chomping = match s.len() - s.trim_end_matches('\n').len() {
+                    0 => "-",
+                    1 => "",
+                    _ => "+",
+                };
+                self.word(&format!("|{}", chomping));
+                self.trailing(trailing);
+                self.out.push('\n');
+                for line in s.lines() {
+                    if !line.is_empty() {
+                        self.indent(indent + 2);
+                        self.out.push_str(line);
+                    }
+                    self.out.push('\n');
+                }
+                self.keeps_blank_lines = chomping == "+";
+            }
+            value => {
+                self.word(&scalar_text(value, false));
+                self.trailing(trailing);
+                self.out.push('\n');
+            }
+        }
+    }
+
+    /// Key einer Block-Map; Collections als Keys im Flow-Stil
+    fn key(&mut self, key: &'a Node) {
+        if let Some(name) = self.alias(key) {
+            // Ohne Leerzeichen gehörte der `:` zum Namen
+            self.word(&format!("*{} ", name));
+            return;
+        }
+        self.properties(key);
+        self.word(&flow_text(key));
+    }
+
+    /// `&anker` und Tag vor dem Wert
+    fn properties(&mut self, node: &'a Node) {
+        if let Some(Anchor::Define(name)) = node.anchor.as_deref()
+            && is_anchor_name(name)
+        {
+            self.anchors.insert(name, node);
+            self.word(&format!("&{}", name));
+        }
+        if let Some(tag) = &node.tag {
+            self.word(tag);
+        }
+    }
+
+    /// Name, unter dem der Knoten als `*alias` geschrieben werden kann
+    fn alias(&self, node: &'a Node) -> Option<&'a str> {
+        let name = match node.anchor.as_deref()? {
+            Anchor::Alias(name) | Anchor::Define(name) => name.as_str(),
+        };
+        self.anchors.get(name).is_some_and(|anchored| *anchored == node).then_some(name)
+    }
+
+    /// Nicht-leere Collection ohne Anker und Tag
+    fn is_compact(&self, node: &'a Node) -> bool {
+        let defines = matches!(node.anchor.as_deref(), Some(Anchor::Define(name)) if is_anchor_name(name));
+        node.tag.is_none() && !defines && self.alias(node).is_none()
+    }
+
+    /// Kommentare eines kompakt verschachtelten Eintrags; die Zeile selbst
+    /// beginnt erst mit seinem ersten Eintrag
+    fn compact_start(&mut self, indent: usize, comment: Option<&Comment>) {
+        let line = self.pending_line.take().unwrap_or(indent);
+        self.comment_lines(line, comment);
+        self.pending_line = Some(line);
+    }
+
+    /// Kommentare, dann Einrückung samt ausstehender `- `
+    fn line_start(&mut self, indent: usize, comment: Option<&Comment>) {
+        let line = self.pending_line.take().unwrap_or(indent);
+        self.comment_lines(line, comment);
+        self.indent(line);
+        for _ in 0..(indent - line) / 2 {
+            self.out.push_str("- ");
+        }
+    }
+
+    fn comment_lines(&mut self, indent: usize, comment: Option<&Comment>) {
+        let keeps_blank_lines = std::mem::take(&mut self.keeps_blank_lines);
+        let Some(comment) = comment else { return };
+
+        if !self.out.is_empty() && !keeps_blank_lines {
+            self.out.extend(std::iter::repeat_n('\n', comment.blank_lines));
+        }
+        for line in &comment.before {
+            self.indent(indent);
+            self.out.push('#');
+            self.out.push_str(line);
+            self.out.push('\n');
+        }
+        if !comment.before.is_empty() {
+            self.out.extend(std::iter::repeat_n('\n', comment.blank_lines_after));
+        }
+    }
+
+    fn indent(&mut self, indent: usize) {
+        self.out.extend(std::iter::repeat_n(' ', indent));
+    }
+
+    /// Text auf der aktuellen Zeile, mit Leerzeichen abgesetzt
+    fn word(&mut self, text: &str) {
+        if !(self.out.is_empty() || self.out.ends_with('\n') || self.out.ends_with(' ')) {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+    }
+
+    fn trailing(&mut self, trailing: Option<&str>) {
+        if let Some(text) = trailing {
+            self.out.push_str(" #");
+            self.out.push_str(text);
+        }
+    }
+
+    fn end_line(&mut self) {
+        if !(self.out.is_empty() || self.out.ends_with('\n')) {
+            self.out.push('\n');
+        }
+    }
+}
+
+/// Knoten in einer Zeile, Collections im Flow-Stil (`[a, b]`, `{a: 1}`)
+fn flow_text(node: &Node) -> String {
+    let text = match &node.value {
+        Value::Array(items) => format!("[{}]", items.iter().map(flow_text).collect::<Vec<_>>().join(", ")),
+        Value::Map(map) => {
+            let entries: Vec<String> =
+                map.iter().map(|(key, value)| format!("{}: {}", flow_text(key), flow_text(value))).collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        value => scalar_text(value, true),
+    };
+    match &node.tag {
+        Some(tag) if matches!(node.value, Value::Array(_) | Value::Map(_)) => format!("{} {}", tag, text),
+        _ => text,
+    }
+}
+
+/// Scalar in einer Zeile (leere Collections als `[]` / `{}`)
+fn scalar_text(value: &Value, flow: bool) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Integer(i) => i.to_string(),
+        Value::BigInt(s) | Value::Decimal(s) => s.clone(),
+        Value::Float(f) if f.is_nan() => ".nan".to_string(),
+        Value::Float(f) if f.is_infinite() => if *f > 0.0 { ".inf" } else { "-.inf" }.to_string(),
+        Value::Float(f) => format!("{:?}", f),
+        // Strings, die wie Zeitstempel aussehen, landen in Anführungszeichen
+        // (siehe `is_plain`) und werden beim Lesen nicht zum Datetime
+        Value::String(s) => quote(s, flow),
+        // Reine Uhrzeiten sind kein YAML-Zeitstempel (auch nicht mit
+        // `!!timestamp`), daher als String
+        Value::DateTime(dt) if dt.date.is_none() => quote(&dt.to_string(), flow),
+        Value::DateTime(dt) => dt.to_string(),
+        Value::Bytes(_) => quote(&crate::document::scalar_to_string(value).unwrap_or_default(), flow),
+        Value::Array(_) => "[]".to_string(),
+        Value::Map(_) => "{}".to_string(),
+    }
+}
+
+/// Mehrzeiliger String, der sich als `|`-Block schreiben lässt
+fn is_literal(s: &str) -> bool {
+    s.contains('\n')
+        && !s.chars().any(|c| c != '\n' && needs_escape(c))
+        // Die Einrückung des Blocks ergibt sich aus der ersten Zeile mit Inhalt
+        && !s.trim_start_matches('\n').starts_with(' ')
+}
+
+/// String plain, in `'...'` oder, mit Steuerzeichen, in `"..."`
+fn quote(s: &str, flow: bool) -> String {
+    if is_plain(s, flow) {
+        s.to_string()
+    } else if s.chars().any(needs_escape) {
+        double_quoted(s)
+    } else {
+        format!("'{}'", s.replace('\'', "''"))
+    }
+}
+
+/// Bleibt der String ohne Anführungszeichen ein String mit gleichem Inhalt?
+///
+/// Strings, die andere Parser nach YAML 1.1 als Bool oder Zahl lesen
+/// (`yes`, `off`, `1_000`, `12:30`), bekommen ebenfalls Anführungszeichen.
+fn is_plain(s: &str, flow: bool) -> bool {
+    let mut chars = s.chars();
+    let (Some(first), second) = (chars.next(), chars.next()) else {
+        return false;
+    };
+    let is_flow_indicator = |c: char| matches!(c, ',' | '[' | ']' | '{' | '}');
+    let indicator = match first {
+        '-' | '?' | ':' => second.is_none_or(|c| c == ' ' || (flow && is_flow_indicator(c))),
+        '#' | '&' | '*' | '!' | '|' | '>' | '\'' | '"' | '%' | '@' | '`' => true,
+        c => is_flow_indicator(c),
+    };
+
+    !indicator
+        && !first.is_whitespace()
+        && !s.ends_with(char::is_whitespace)
+        && !s.chars().any(needs_escape)
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.ends_with(':')
+        && !(flow && s.contains(is_flow_indicator))
+        && !s.starts_with("---")
+        && !s.starts_with("...")
+        && matches!(resolve_plain(s), Value::String(_))
+        && !is_yaml11_scalar(s)
+}
+
+/// Bools und Zahlen, die es nur in YAML 1.1 gibt
+fn is_yaml11_scalar(s: &str) -> bool {
+    const BOOLS: [&str; 16] =
+        ["y", "Y", "yes", "Yes", "YES", "n", "N", "no", "No", "NO", "on", "On", "ON", "off", "Off", "OFF"];
+    let numeric = s.bytes().any(|b| b.is_ascii_digit())
+        && s.bytes().all(|b| b.is_ascii_digit() || matches!(b, b'_' | b':' | b'.' | b'+' | b'-'))
+        && s.contains(['_', ':']);
+    let binary = s.strip_prefix("0b").is_some_and(|bits| !bits.is_empty() && bits.bytes().all(|b| matches!(b, b'0' | b'1')));
+    BOOLS.contains(&s) || numeric || binary
+}
+
+/// Zeichen, die nur mit Escape in `"..."` stehen können
+fn needs_escape(c: char) -> bool {
+    c.is_control() || matches!(c, '\u{feff}' | '\u{2028}' | '\u{2029}')
+}
+
+fn double_quoted(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\0"),
+            c if needs_escape(c) && (c as u32) < 0x100 => out.push_str(&format!("\\x{:02X}", c as u32)),
+            c if needs_escape(c) => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Nur Namen, die ohne Escaping als `&name` / `*name` stehen können
+fn is_anchor_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
 
 // ============================================================================
 // STRING-ZU-STRING FUNKTIONEN (Core-Logik für CLI und Web)
@@ -10,103 +1145,22 @@ use crate::formats::utils::json_to_toml_value;
 
 /// Konvertiert YAML String zu JSON String
 pub fn yaml_to_json_string(input: &str) -> Result<String, FormatError> {
-    let yaml_value: serde_yaml::Value = serde_yaml::from_str(input)
-        .map_err(|e| FormatError::ParseError(format!("Ungültiges YAML: {}", e)))?;
-    
-    let json_value = serde_json::to_value(&yaml_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Konvertieren: {}", e)))?;
-    
-    serde_json::to_string_pretty(&json_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von JSON: {}", e)))
+    write_json(&read_yaml(input)?)
 }
 
-/// Konvertiert YAML String zu YAML String (Pretty-Printing)
+/// Konvertiert YAML String zu YAML String (Formatierung, Kommentare bleiben)
 pub fn yaml_to_yaml_string(input: &str) -> Result<String, FormatError> {
-    let yaml_value: serde_yaml::Value = serde_yaml::from_str(input)
-        .map_err(|e| FormatError::ParseError(format!("Ungültiges YAML: {}", e)))?;
-    
-    serde_yaml::to_string(&yaml_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von YAML: {}", e)))
+    write_yaml(&read_yaml(input)?)
 }
 
 /// Konvertiert YAML String zu TOML String
 pub fn yaml_to_toml_string(input: &str) -> Result<String, FormatError> {
-    let yaml_value: serde_yaml::Value = serde_yaml::from_str(input)
-        .map_err(|e| FormatError::ParseError(format!("Ungültiges YAML: {}", e)))?;
-    
-    let json_value = serde_json::to_value(&yaml_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Konvertieren: {}", e)))?;
-    
-    // TOML kann kein Array als Root haben
-    let toml_ready_value = if json_value.is_array() {
-        let mut root = serde_json::Map::new();
-        root.insert("data".to_string(), json_value);
-        serde_json::Value::Object(root)
-    } else {
-        json_value
-    };
-    
-    let toml_value = json_to_toml_value(&toml_ready_value)?;
-    
-    toml::to_string_pretty(&toml_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von TOML: {}", e)))
+    write_toml(&read_yaml(input)?)
 }
 
 /// Konvertiert YAML String zu CSV String
 pub fn yaml_to_csv_string(input: &str) -> Result<String, FormatError> {
-    let yaml_value: serde_yaml::Value = serde_yaml::from_str(input)
-        .map_err(|e| FormatError::ParseError(format!("Ungültiges YAML: {}", e)))?;
-    
-    let json_value = serde_json::to_value(&yaml_value)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Konvertieren: {}", e)))?;
-    
-    // Prüfen ob es ein Array ist
-    let array = match json_value {
-        serde_json::Value::Array(arr) => arr,
-        serde_json::Value::Object(_) => vec![json_value],
-        _ => return Err(FormatError::SerializationError("YAML muss ein Array oder Objekt sein".to_string()))
-    };
-    
-    if array.is_empty() {
-        return Ok(String::new());
-    }
-    
-    // Alle Objekte flattenen
-    let flattened: Vec<_> = array.iter()
-        .map(|v| flatten_json_value(v, ""))
-        .collect();
-    
-    // Header sammeln
-    let mut all_headers = std::collections::BTreeSet::new();
-    for obj in &flattened {
-        for key in obj.keys() {
-            all_headers.insert(key.clone());
-        }
-    }
-    let headers: Vec<String> = all_headers.into_iter().collect();
-    
-    // CSV Writer in Memory
-    let mut writer = csv::Writer::from_writer(vec![]);
-    
-    // Header schreiben
-    writer.write_record(&headers)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Schreiben der CSV-Header: {}", e)))?;
-    
-    // Daten schreiben
-    for flat_obj in flattened {
-        let row: Vec<String> = headers.iter()
-            .map(|h| flat_obj.get(h).cloned().unwrap_or_default())
-            .collect();
-        writer.write_record(&row)
-            .map_err(|e| FormatError::SerializationError(format!("Fehler beim Schreiben der CSV-Zeile: {}", e)))?;
-    }
-    
-    // Writer in String umwandeln
-    let data = writer.into_inner()
-        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Abschliessen von CSV: {}", e)))?;
-    
-    String::from_utf8(data)
-        .map_err(|e| FormatError::SerializationError(format!("Fehler bei UTF-8 Konvertierung: {}", e)))
+    write_csv(&read_yaml(input)?)
 }
 
 // ============================================================================
@@ -115,134 +1169,177 @@ pub fn yaml_to_csv_string(input: &str) -> Result<String, FormatError> {
 
 /// Konvertiert YAML zu JSON (File-I/O Wrapper)
 pub fn convert_yaml_to_json(
-    input_path: &str,
-    output_path: &str
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = yaml_to_json_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
 
 /// Konvertiert YAML zu YAML (File-I/O Wrapper)
 pub fn convert_yaml_to_yaml(
-    input_path: &str,
-    output_path: &str
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = yaml_to_yaml_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
 
 /// Konvertiert YAML zu TOML (File-I/O Wrapper)
 pub fn convert_yaml_to_toml(
-    input_path: &str,
-    output_path: &str
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = yaml_to_toml_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
 
 /// Konvertiert YAML zu CSV (File-I/O Wrapper)
 pub fn convert_yaml_to_csv(
-    input_path: &str,
-    output_path: &str
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>
 ) -> Result<(), FormatError> {
+    let (input_path, output_path) = (input_path.as_ref(), output_path.as_ref());
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
     
     let result = yaml_to_csv_string(&content)?;
     
     fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path.display(), e)))?;
     
     Ok(())
 }
 
-/// Flattened ein JSON-Objekt zu einer flachen Map mit Unterstrich-Trennzeichen
-/// 
-/// Beispiel:
-/// Input:  {"contact": {"email": "test@test.com", "phone": "+49"}}
-/// Output: {"contact_email": "test@test.com", "contact_phone": "+49"}
-fn flatten_json_value(value: &serde_json::Value, prefix: &str) -> std::collections::HashMap<String, String> {
-    use std::collections::HashMap;
-    
-    let mut result = HashMap::new();
-    
-    match value {
-        serde_json::Value::Object(obj) => {
-            for (key, val) in obj {
-                let new_key = if prefix.is_empty() {
-                    key.clone()
-                } else {
-                    format!("{}_{}", prefix, key)
-                };
-                
-                // Rekursiv für verschachtelte Objekte
-                if val.is_object() {
-                    let nested = flatten_json_value(val, &new_key);
-                    result.extend(nested);
-                } else {
-                    result.insert(new_key, json_value_to_string(val));
-                }
-            }
-        }
-        _ => {
-            // Primitive Werte direkt einfügen
-            if !prefix.is_empty() {
-                result.insert(prefix.to_string(), json_value_to_string(value));
-            }
-        }
-    }
-    
-    result
-}
-
-/// Konvertiert einen JSON-Wert zu einem String
-fn json_value_to_string(value: &serde_json::Value) -> String {
-    match value {
-        serde_json::Value::String(s) => s.clone(),
-        serde_json::Value::Number(n) => n.to_string(),
-        serde_json::Value::Bool(b) => b.to_string(),
-        serde_json::Value::Null => String::new(),
-        _ => serde_json::to_string(value).unwrap_or_default(),
-    }
-}
-
 /// Validiert eine YAML-Datei ohne sie zu schreiben
 /// Ähnlich wie validate_json und validate_toml, aber für YAML
 /// 
-/// Diese Funktion ist zentral für alle YAML-Konvertierungen:
-/// Sie liest die Datei, parst sie und gibt ein serde_yaml::Value zurück
-pub fn validate_yaml(input_path: &str) -> Result<serde_yaml::Value, FormatError> {
+/// Sie liest die Datei, parst sie mit `read_yaml` und gibt ein serde_yaml::Value zurück
+pub fn validate_yaml(input_path: impl AsRef<Path>) -> Result<serde_yaml::Value, FormatError> {
+    let input_path = input_path.as_ref();
     // 1. Datei lesen - content enthält den YAML-Text als String
     let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path.display(), e)))?;
 
-    // 2. YAML parsen und validieren
-    // serde_yaml::from_str wandelt den String in eine serde_yaml::Value Struktur um
-    // Dies ist die "Intermediate Representation" (IR)
-    let yaml_value: serde_yaml::Value = serde_yaml::from_str(&content)
-        .map_err(|e| FormatError::ParseError(format!("Fehler beim Parsen von YAML: {}", e)))?;
+    // 2. YAML mit demselben Reader wie bei der Konvertierung parsen
+    // (Core-Schema, doppelte Keys sind Fehler, mehrere Dokumente ergeben ein Array)
+    let doc = read_yaml(&content)?;
 
     // 3. Validiertes serde_yaml::Value zurückgeben
-    Ok(yaml_value)
-}
\ No newline at end of file
+    Ok(node_to_yaml_value(&doc.root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comments_carried_into_toml() {
+        let input = "# Server\nserver:\n  port: 80  # HTTP\n  motd: |\n    # kein Kommentar\n  url: \"a#b\" # Quote\n";
+        let doc = read_yaml(input).unwrap();
+
+        let server = doc.root.as_map().unwrap().iter().next().unwrap().0;
+        assert_eq!(server.comment.as_ref().unwrap().before, [" Server"]);
+
+        assert_eq!(
+            write_toml(&doc).unwrap(),
+            "# Server\n[server]\nport = 80 # HTTP\nmotd = \"\"\"\n# kein Kommentar\n\"\"\"\nurl = \"a#b\" # Quote\n"
+        );
+    }
+    #[test]
+    fn test_comments_written_back_to_yaml() {
+        let input = "# Server\nserver:\n  port: 80  # HTTP\nitems:\n# erster\n- name: a # Name\n  id: 1\n";
+        let options = ConvertOptions { sort_keys: true, ..ConvertOptions::default() };
+        let (yaml, _) = crate::warning::convert_reporting(&YamlFormat, &YamlFormat, input, &options).unwrap();
+        assert_eq!(
+            yaml,
+            "items:\n- id: 1\n  # erster\n  name: a # Name\n# Server\nserver:\n  port: 80 # HTTP\n"
+        );
+        let mut sorted = read_yaml(input).unwrap();
+        sorted.sort_keys();
+        assert_eq!(read_yaml(&yaml).unwrap(), sorted);
+
+        // Kommentar über dem ersten Key eines Eintrags steht vor dem "- "
+        let yaml = yaml_to_yaml_string(input).unwrap();
+        assert_eq!(yaml, "# Server\nserver:\n  port: 80 # HTTP\nitems:\n# erster\n- name: a # Name\n  id: 1\n");
+    }
+
+    #[test]
+    fn test_yaml_round_trip_keeps_layout() {
+        let input = "# Kopf\n\nbase: &base\n  a: 1\n\nlist:\n- 1 # eins\n\n# zwei\n- 2\n- *base\n";
+        assert_eq!(yaml_to_yaml_string(input).unwrap(), input);
+
+        // Keys und Strings wie die früheren internen Marker bleiben unverändert
+        let input = "__asp_comment__0: null\ntag: '!__asp_raw__ 5'\n";
+        assert_eq!(yaml_to_yaml_string(input).unwrap(), input);
+    }
+
+    #[test]
+    fn test_multi_document_stream() {
+        let input = "kind: Service\n---\n# Pod\nkind: Pod\n---\n";
+        let doc = read_yaml(input).unwrap();
+        let documents = doc.root.as_array().unwrap();
+        assert_eq!(documents.len(), 3);
+        assert!(documents[2].is_null());
+
+        let stream = write_yaml_with(&doc, &YamlOptions { multi_document: true, ..YamlOptions::default() }).unwrap();
+        assert_eq!(stream, "kind: Service\n---\n# Pod\nkind: Pod\n---\nnull\n");
+        assert_eq!(read_yaml(&stream).unwrap(), doc);
+
+        // Ohne Option bleibt es ein einzelnes Dokument mit Sequenz
+        assert!(write_yaml(&doc).unwrap().starts_with("- kind: Service\n"));
+    }
+    #[test]
+    fn test_merge_keys_and_aliases() {
+        let input = "base: &base {a: 1, b: 2}\nextra: &extra {b: 3, c: 4}\njob:\n  <<: [*base, *extra]\n  c: 5\n";
+        let doc = read_yaml(input).unwrap();
+        assert_eq!(
+            write_json(&Document::new(doc.root.as_map().unwrap().get("job").unwrap().clone())).unwrap(),
+            "{\n  \"a\": 1,\n  \"b\": 2,\n  \"c\": 5\n}"
+        );
+
+        // Mit keep_aliases bleiben Anker, Aliase und Merge-Keys erhalten
+        let options = YamlOptions { keep_aliases: true, ..YamlOptions::default() };
+        let kept = read_yaml_with("base: &base\n  a: 1\njob:\n  <<: *base\nlist:\n- &v x\n- *v\n", &options).unwrap();
+        assert_eq!(
+            write_yaml_with(&kept, &options).unwrap(),
+            "base: &base\n  a: 1\njob:\n  <<: *base\nlist:\n- &v x\n- *v\n"
+        );
+
+        assert!(read_yaml("job:\n  <<: [1, 2]\n").is_err());
+    }
+
+    #[test]
+    fn test_alias_bomb_limit() {
+        let input = "a: &a [x, x, x, x]\nb: &b [*a, *a, *a, *a]\nc: [*b, *b, *b, *b]\n";
+        assert!(read_yaml(input).is_ok());
+
+        let options = YamlOptions { max_alias_nodes: 50, ..YamlOptions::default() };
+        let err = read_yaml_with(input, &options).unwrap_err();
+        assert!(err.to_string().contains("Alias-Bomben"));
+    }
+}
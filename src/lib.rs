@@ -2,10 +2,21 @@ pub mod error;
 pub mod formats;
 pub mod detect;
 pub mod format;
+pub mod document;
+pub mod registry;
+pub mod options;
+pub mod warning;
+pub mod stream;
+pub mod typed;
 
 // Re-exports für einfachen Zugriff
 pub use error::FormatError;
 pub use format::FileFormat;
+pub use document::{Document, Node, Value};
+pub use registry::{Capabilities, Format, FormatRegistry};
+pub use options::{ConvertOptions, NullPolicy, NumberPolicy};
+pub use warning::{convert_reporting, write_reporting, ConversionWarning, WarningKind};
+pub use typed::{from_str, from_str_with, to_string};
 
 #[cfg(feature = "cli")]
 pub mod cli;
\ No newline at end of file
@@ -1,20 +1,30 @@
 // CLI-Interface - nur mit "cli" Feature kompiliert
 
 #[cfg(feature = "cli")]
-use asp_cli::{FileFormat, FormatError};
+use asp_cli::{convert_reporting, write_reporting, ConvertOptions, Document, Format, FormatError, FormatRegistry, Value};
 #[cfg(feature = "cli")]
-use std::path::Path;
+use asp_cli::cli::{display_name, numbered_path, read_input, render_error, render_warning, resolve_input_format, write_output, OutputStream, STDIO};
+#[cfg(feature = "cli")]
+use asp_cli::cli::args::{convert_options, CsvArgs, WriteArgs, YamlArgs};
+#[cfg(feature = "cli")]
+use asp_cli::cli::batch::{convert_batch, render_summary, BatchStatus};
+#[cfg(feature = "cli")]
+use asp_cli::cli::edit::{edit_file, EditOps};
+#[cfg(feature = "cli")]
+use asp_cli::cli::validate::validate_files;
 #[cfg(feature = "cli")]
-use std::fs;
+use asp_cli::stream::{convert_stream, convert_stream_two_pass};
 #[cfg(feature = "cli")]
-use std::str::FromStr;
+use std::io::BufReader;
+#[cfg(feature = "cli")]
+use std::path::Path;
 #[cfg(feature = "cli")]
 use clap::{Parser, Subcommand};
 
 #[cfg(feature = "cli")]
 #[derive(Parser)]
 #[command(name = "asp_cli")]
-#[command(about = "Format-Konverter für JSON, YAML, TOML, CSV")]
+#[command(about = "Format-Konverter für JSON, YAML, TOML, CSV, XML")]
 #[command(version = "0.1.0")]
 struct Cli {
     #[command(subcommand)]
@@ -26,65 +36,326 @@ struct Cli {
 enum Commands {
     /// Konvertiert von einem Format zu einem anderen
     Convert {
-        /// Eingabedatei
-        #[arg(short, long)]
+        /// Eingabedatei oder, mit --out-dir, Glob-Muster bzw. Ordner (z.B. "configs/**/*.yaml")
+        #[arg(value_name = "INPUT", conflicts_with = "input")]
+        pattern: Option<String>,
+        
+        /// Eingabedatei, "-" für stdin (ohne bekannte Extension wird das Format am Inhalt erkannt)
+        #[arg(short, long, default_value = STDIO)]
         input: String,
         
-        /// Ausgabedatei
-        #[arg(short, long)]
+        /// Ausgabedatei, "-" für stdout
+        #[arg(short, long, default_value = STDIO)]
         output: String,
+        
+        /// Eingabeformat (überschreibt Extension und Erkennung)
+        #[arg(long, value_name = "FORMAT")]
+        from: Option<String>,
+        
+        /// Ausgabeformat (überschreibt die Extension der Ausgabedatei)
+        #[arg(long, value_name = "FORMAT")]
+        to: Option<String>,
+        
+        /// Batch-Modus: alle passenden Dateien in diesen Ordner konvertieren
+        #[arg(long, value_name = "DIR", requires = "to", conflicts_with_all = ["output", "from"])]
+        out_dir: Option<String>,
+        
+        /// Batch-Modus: auch unveränderte Dateien neu konvertieren
+        #[arg(long, requires = "out_dir")]
+        force: bool,
+        
+        /// Root-Array (z.B. mehrere YAML-Dokumente) in eine Datei pro Eintrag aufteilen: out.toml → out-1.toml, out-2.toml, ...
+        #[arg(long, conflicts_with = "out_dir")]
+        split: bool,
+        
+        #[command(flatten)]
+        csv: CsvArgs,
+        
+        #[command(flatten)]
+        yaml: YamlArgs,
+        
+        #[command(flatten)]
+        write: WriteArgs,
+    },
+    
+    /// Prüft die Syntax von Dateien, ohne etwas zu schreiben
+    Validate {
+        /// Dateien oder Glob-Muster (z.B. "config/**/*.yaml"), "-" für stdin
+        #[arg(required = true)]
+        files: Vec<String>,
+        
+        /// Format für alle Dateien (überschreibt Extension und Erkennung)
+        #[arg(long, value_name = "FORMAT")]
+        from: Option<String>,
+        
+        #[command(flatten)]
+        csv: CsvArgs,
+        
+        #[command(flatten)]
+        yaml: YamlArgs,
+    },
+    
+    /// Ändert eine TOML-Datei, Kommentare und Layout bleiben erhalten
+    Edit {
+        /// TOML-Datei, "-" für stdin
+        file: String,
+        
+        /// Wert setzen, z.B. --set server.port=8080 (Wert als TOML, Strings in Anführungszeichen)
+        #[arg(long, value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        
+        /// String setzen, ohne ihn als TOML zu lesen, z.B. --set-string server.host=localhost
+        #[arg(long, value_name = "KEY=VALUE")]
+        set_string: Vec<String>,
+        
+        /// Key entfernen, z.B. --unset server.debug
+        #[arg(long, value_name = "KEY")]
+        unset: Vec<String>,
+        
+        /// Datei (JSON, YAML, TOML, ...) hineinmergen, Tabellen werden zusammengeführt
+        #[arg(long, value_name = "FILE")]
+        merge: Vec<String>,
+        
+        /// Ausgabedatei, "-" für stdout
+        #[arg(short, long, default_value = STDIO, conflicts_with = "in_place")]
+        output: String,
+        
+        /// Datei direkt überschreiben
+        #[arg(long)]
+        in_place: bool,
     },
 }
 
 #[cfg(feature = "cli")]
-/// Elegante Konvertierung mit FileFormat Enum (viel sauberer als verschachtelte Matches!)
+/// Konvertierung über die FormatRegistry
+///
+/// Formate kommen aus `--from`/`--to`, sonst aus der Dateiendung. Beim Input
+/// (auch stdin) wird zuletzt das Format am Inhalt erkannt.
 fn convert_based_on_extension(
+    registry: &FormatRegistry,
     input_path: &str,
     output_path: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+    split: bool,
+    options: &ConvertOptions,
 ) -> Result<(), FormatError> {
-    // 1. Parse Extensions zu FileFormat Enum
-    let input_ext = Path::new(input_path)
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .ok_or_else(|| FormatError::ParseError("Keine Eingabedatei-Extension gefunden".to_string()))?;
+    // 1. Ausgabeformat bestimmen
+    let output_format = match to {
+        Some(name) => registry.resolve(name)?,
+        None if output_path == STDIO => {
+            return Err(FormatError::InvalidFormat(
+                "Ausgabe nach stdout braucht ein Format - bitte --to angeben".to_string(),
+            ));
+        }
+        None => registry.for_path(output_path).ok_or_else(|| {
+            FormatError::InvalidFormat(format!(
+                "Ausgabeformat für {} unbekannt - bitte --to angeben",
+                output_path
+            ))
+        })?,
+    };
     
-    let output_ext = Path::new(output_path)
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .ok_or_else(|| FormatError::ParseError("Keine Ausgabedatei-Extension gefunden".to_string()))?;
+    // Dateiname als TOML-Key für Root-Arrays (--root-key-from-input), nicht bei stdin
+    let options = &if input_path == STDIO { options.clone() } else { options.for_input(Path::new(input_path)) };
     
-    let input_format = FileFormat::from_str(input_ext)?;
-    let output_format = FileFormat::from_str(output_ext)?;
+    // Ist das Eingabeformat ohne Inhalt bekannt, wird gestreamt: Datensatz-Formate
+    // (NDJSON, CSV, JSON-Arrays) laufen dann Datensatz für Datensatz durch
+    let known_format = match from {
+        Some(name) => Some(registry.resolve(name)?),
+        None if input_path != STDIO => registry.for_path(input_path),
+        None => None,
+    };
+    // (nicht bei gleicher Ein- und Ausgabedatei, die würde beim Schreiben geleert)
+    if let Some(input_format) = known_format
+        && !split
+        && distinct_files(input_path, output_path)
+    {
+        return convert_streaming(input_path, output_path, input_format, output_format, options);
+    }
     
-    // 2. Datei lesen
-    let content = fs::read_to_string(input_path)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+    // 2. Datei bzw. stdin lesen und Eingabeformat bestimmen
+    let content = read_input(input_path)?;
+    let input_format = resolve_input_format(registry, input_path, from, &content)?;
     
-    // 3. Konvertierung durchführen (eine Zeile!)
-    let result = input_format.convert(&content, output_format)?;
+    // 3. Konvertierung durchführen: Lesen → Zwischenmodell → Schreiben
+    if split {
+        return convert_split(input_path, output_path, input_format.read_with(&content, options)?, output_format, options);
+    }
+    let (result, warnings) = convert_reporting(input_format, output_format, &content, options)?;
     
-    // 4. Datei schreiben
-    fs::write(output_path, result)
-        .map_err(|e| FormatError::IoError(format!("Fehler beim Schreiben nach {}: {}", output_path, e)))?;
+    // Warnungen immer auf stderr, damit sie nicht in der Pipe landen
+    for warning in &warnings {
+        eprintln!("{}", render_warning(warning, display_name(input_path)));
+    }
     
+    // 4. Datei bzw. stdout schreiben
+    write_output(output_path, &result)
+}
+
+#[cfg(feature = "cli")]
+/// Ein- und Ausgabe sind sicher verschiedene Dateien
+///
+/// Verglichen werden die kanonischen Pfade (wie bei `FileFormat::convert_file_with`),
+/// damit auch `./a.json` und `a.json` oder Symlinks erkannt werden. Lässt sich
+/// das nicht klären, gilt die Datei als dieselbe.
+fn distinct_files(input_path: &str, output_path: &str) -> bool {
+    if input_path == STDIO || output_path == STDIO {
+        return true;
+    }
+    match (Path::new(input_path).canonicalize(), Path::new(output_path).canonicalize()) {
+        (Ok(input), Ok(output)) => input != output,
+        // Die Ausgabe gibt es noch nicht
+        (_, Err(e)) => e.kind() == std::io::ErrorKind::NotFound,
+        (Err(_), Ok(_)) => false,
+    }
+}
+
+#[cfg(feature = "cli")]
+/// Konvertierung als Stream (siehe `convert_stream`), Warnungen am Ende
+fn convert_streaming(
+    input_path: &str,
+    output_path: &str,
+    input_format: &dyn Format,
+    output_format: &dyn Format,
+    options: &ConvertOptions,
+) -> Result<(), FormatError> {
+    let mut output = OutputStream::new(output_path);
+    let warnings = if input_path == STDIO {
+        convert_stream(input_format, output_format, &mut std::io::stdin().lock(), &mut output, options)?
+    } else {
+        // Dateien lassen sich zurückspulen, z.B. für alle CSV-Spalten vorab
+        let file = std::fs::File::open(input_path)
+            .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen von {}: {}", input_path, e)))?;
+        convert_stream_two_pass(input_format, output_format, &mut BufReader::new(file), &mut output, options)?
+    };
+    output.finish()?;
+    
+    for warning in &warnings {
+        eprintln!("{}", render_warning(warning, display_name(input_path)));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+/// `convert --split`: jeder Eintrag eines Root-Arrays wird eine eigene Datei
+/// (out-1.toml, out-2.toml, ...), andere Dokumente werden normal geschrieben
+fn convert_split(
+    input_path: &str,
+    output_path: &str,
+    document: Document,
+    output_format: &dyn Format,
+    options: &ConvertOptions,
+) -> Result<(), FormatError> {
+    if output_path == STDIO {
+        return Err(FormatError::InvalidFormat("--split braucht eine Ausgabedatei (-o)".to_string()));
+    }
+    
+    let parts = match document.root.value {
+        Value::Array(items) => items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| (numbered_path(output_path, i + 1), Document::new(item)))
+            .collect(),
+        _ => vec![(output_path.to_string(), document)],
+    };
+    
+    for (path, part) in parts {
+        let (result, warnings) = write_reporting(output_format, &part, options)?;
+        for warning in &warnings {
+            eprintln!("{}", render_warning(warning, display_name(input_path)));
+        }
+        write_output(&path, &result)?;
+        println!("✓ Konvertierung erfolgreich: {} -> {}", display_name(input_path), path);
+    }
     Ok(())
 }
 
+#[cfg(feature = "cli")]
+/// Batch-Modus von `convert`: Tabelle ausgeben, Details zu Fehlern danach
+fn run_batch(registry: &FormatRegistry, pattern: &str, to: &str, out_dir: &str, force: bool, options: &ConvertOptions) {
+    let result = registry
+        .resolve(to)
+        .and_then(|format| convert_batch(registry, pattern, format, Path::new(out_dir), force, options));
+    
+    let entries = match result {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}", render_error(&e, pattern));
+            std::process::exit(1);
+        }
+    };
+    
+    println!("{}", render_summary(&entries));
+    
+    let mut failed = false;
+    for entry in &entries {
+        for warning in &entry.warnings {
+            eprintln!("\n{}", render_warning(warning, &entry.input.display().to_string()));
+        }
+        if let BatchStatus::Failed(e) = &entry.status {
+            eprintln!("\n{}", render_error(e, &entry.input.display().to_string()));
+            failed = true;
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
 #[cfg(feature = "cli")]
 fn main() {
     // clap parst automatisch die Argumente
     let cli = Cli::parse();
+    let registry = FormatRegistry::default();
     
     match cli.command {
-        Commands::Convert { input, output } => {
-            match convert_based_on_extension(&input, &output) {
-                Ok(_) => println!("✓ Konvertierung erfolgreich: {} -> {}", input, output),
+        Commands::Convert { pattern, input, output, from, to, out_dir, force, split, csv, yaml, write } => {
+            let input = pattern.unwrap_or(input);
+            let options = convert_options(&csv, &yaml, &write);
+            
+            if let Some(out_dir) = out_dir {
+                run_batch(&registry, &input, to.as_deref().unwrap_or_default(), &out_dir, force, &options);
+                return;
+            }
+            
+            match convert_based_on_extension(&registry, &input, &output, from.as_deref(), to.as_deref(), split, &options) {
+                // Bei stdout keine Statusmeldung, sonst landet sie in der Pipe
+                Ok(_) if output == STDIO || split => {}
+                Ok(_) => println!("✓ Konvertierung erfolgreich: {} -> {}", display_name(&input), output),
                 Err(e) => {
-                    eprintln!("✗ Fehler: {}", e);
+                    eprintln!("{}", render_error(&e, display_name(&input)));
                     std::process::exit(1);
                 }
             }
         }
+        Commands::Edit { file, set, set_string, unset, merge, output, in_place } => {
+            let output = if in_place { file.clone() } else { output };
+            let ops = EditOps { set, set_string, unset, merge };
+            
+            if let Err(e) = edit_file(&registry, &file, &ops).and_then(|result| write_output(&output, &result)) {
+                eprintln!("{}", render_error(&e, display_name(&file)));
+                std::process::exit(1);
+            }
+        }
+        Commands::Validate { files, from, csv, yaml } => {
+            let options = ConvertOptions { csv: csv.to_options(), yaml: yaml.to_options(), ..ConvertOptions::default() };
+            let reports = validate_files(&registry, &files, from.as_deref(), &options);
+            let invalid = reports.iter().filter(|r| !r.is_valid()).count();
+            
+            for report in &reports {
+                match &report.result {
+                    Ok(format) => println!("✓ {} ({})", report.path, format),
+                    Err(e) => eprintln!("{}\n", render_error(e, &report.path)),
+                }
+            }
+            
+            if invalid > 0 {
+                eprintln!("✗ {} von {} Dateien ungültig", invalid, reports.len());
+                std::process::exit(1);
+            }
+            println!("✓ Alle {} Dateien gültig", reports.len());
+        }
     }
 }
 
@@ -0,0 +1,134 @@
+// Optionen für Konvertierungen - werden über `Format::read_with` und
+// `Format::write_with` an die Reader und Writer durchgereicht
+
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::error::FormatError;
+use crate::formats::csv::CsvOptions;
+use crate::formats::json::JsonOptions;
+use crate::formats::toml::TomlOptions;
+use crate::formats::yaml::YamlOptions;
+
+/// Einstellungen für Lesen und Schreiben
+///
+/// `ConvertOptions::default()` entspricht dem Verhalten von `read`/`write`.
+/// Formate lesen nur die Felder, die sie betreffen.
+///
+/// # Beispiel
+/// ```
+/// use asp_cli::{ConvertOptions, FileFormat};
+/// use asp_cli::formats::csv::CsvOptions;
+///
+/// let options = ConvertOptions {
+///     csv: CsvOptions { delimiter: Some(b';'), ..CsvOptions::default() },
+///     ..ConvertOptions::default()
+/// };
+/// let csv = FileFormat::Json.convert_with(r#"[{"a": 1, "b": 2}]"#, FileFormat::Csv, &options)?;
+/// assert_eq!(csv, "a;b\n1;2\n");
+/// # Ok::<(), asp_cli::FormatError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConvertOptions {
+    /// CSV-Dialekt
+    pub csv: CsvOptions,
+    /// JSON-Ausgabe (Darstellung von Datetimes)
+    pub json: JsonOptions,
+    /// TOML-Ausgabe (Key für Root-Arrays)
+    pub toml: TomlOptions,
+    /// YAML-Eingabe und -Ausgabe (mehrere Dokumente, Aliase)
+    pub yaml: YamlOptions,
+    /// Umgang mit null in Formaten ohne null (TOML)
+    pub null_policy: NullPolicy,
+    /// Umgang mit Zahlen, die das Zielformat nicht exakt darstellen kann (TOML)
+    pub number_policy: NumberPolicy,
+    /// Jede Warnung (siehe `ConversionWarning`) bricht die Konvertierung ab
+    pub strict: bool,
+    /// Keys alphabetisch sortieren statt die Reihenfolge der Quelle zu behalten
+    /// (gilt für `write_reporting` und alle `convert`-Funktionen)
+    pub sort_keys: bool,
+}
+
+impl ConvertOptions {
+    /// Optionen für eine bestimmte Eingabedatei
+    ///
+    /// Mit `toml.root_key_from_input` wird der Dateiname ohne Endung zum
+    /// Key für Root-Arrays (`users.json` → `[[users]]`). Ohne Dateiname
+    /// (z.B. stdin) bleibt `toml.root_key` unverändert.
+    pub fn for_input(&self, path: &Path) -> ConvertOptions {
+        let mut options = self.clone();
+        if self.toml.root_key_from_input
+            && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+        {
+            options.toml.root_key = stem.to_string();
+        }
+        options
+    }
+}
+
+/// Was mit null-Werten passiert, wenn das Zielformat kein null kennt
+///
+/// Als Text (CLI, Web): `omit`, `empty`, `error` oder `sentinel:<TEXT>`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum NullPolicy {
+    /// Key bzw. Array-Eintrag weglassen und eine Warnung mit dem Pfad ausgeben
+    #[default]
+    Omit,
+    /// Leerer String ""
+    EmptyString,
+    /// Fester Ersatz-String, z.B. "NULL"
+    Sentinel(String),
+    /// Konvertierung mit Fehler abbrechen
+    Error,
+}
+
+/// Was mit Zahlen passiert, die das Zielformat nicht darstellen kann
+/// (z.B. Ganzzahlen über i64::MAX oder Dezimalzahlen mit mehr Stellen als
+/// f64 in TOML)
+///
+/// Als Text (CLI, Web): `stringify`, `error` oder `clamp`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum NumberPolicy {
+    /// Als String mit allen Stellen schreiben, mit Warnung
+    #[default]
+    Stringify,
+    /// Konvertierung mit Fehler abbrechen
+    Error,
+    /// Auf den nächsten darstellbaren Wert runden bzw. begrenzen, mit Warnung
+    Clamp,
+}
+
+impl FromStr for NumberPolicy {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stringify" => Ok(NumberPolicy::Stringify),
+            "error" => Ok(NumberPolicy::Error),
+            "clamp" => Ok(NumberPolicy::Clamp),
+            _ => Err(FormatError::InvalidFormat(format!(
+                "Unbekannte Zahlen-Behandlung: {} (erlaubt: stringify, error, clamp)",
+                s
+            ))),
+        }
+    }
+}
+
+impl FromStr for NullPolicy {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "omit" => Ok(NullPolicy::Omit),
+            "empty" => Ok(NullPolicy::EmptyString),
+            "error" => Ok(NullPolicy::Error),
+            _ => match s.strip_prefix("sentinel:") {
+                Some(text) => Ok(NullPolicy::Sentinel(text.to_string())),
+                None => Err(FormatError::InvalidFormat(format!(
+                    "Unbekannte null-Behandlung: {} (erlaubt: omit, empty, error, sentinel:<TEXT>)",
+                    s
+                ))),
+            },
+        }
+    }
+}
@@ -0,0 +1,304 @@
+// Erweiterbares Format-System: Trait + Registry
+//
+// Jedes Format liest in das Zwischenmodell und schreibt daraus. Neue Formate
+// (auch aus anderen Crates) werden einfach registriert, statt FileFormat,
+// FromStr und alle Konvertierungspfade anzupassen.
+
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use crate::document::Document;
+use crate::error::FormatError;
+use crate::format::FileFormat;
+use crate::options::ConvertOptions;
+use crate::stream::{RecordWriter, Records};
+use crate::warning::ConversionWarning;
+
+/// Was ein Format darstellen kann (z.B. für Warnungen bei verlustbehafteten Konvertierungen)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Format kann gelesen werden
+    pub read: bool,
+    /// Format kann geschrieben werden
+    pub write: bool,
+    /// Verschachtelte Objekte und Arrays
+    pub nested: bool,
+    /// Array als Root-Element
+    pub root_array: bool,
+    /// Echter null-Wert
+    pub null: bool,
+    /// Typisierte Datums-/Zeitwerte
+    pub datetime: bool,
+    /// Tags an Werten (z.B. YAML "!secret")
+    pub tags: bool,
+    /// Anker, Aliase und Merge-Keys (z.B. YAML "&name", "*name", "<<")
+    pub aliases: bool,
+}
+
+impl Capabilities {
+    /// Alles wird unterstützt - sinnvoller Ausgangspunkt für eigene Formate
+    pub const FULL: Capabilities = Capabilities {
+        read: true,
+        write: true,
+        nested: true,
+        root_array: true,
+        null: true,
+        datetime: true,
+        tags: true,
+        aliases: true,
+    };
+}
+
+/// Ein Datenformat, das in das Zwischenmodell lesen und daraus schreiben kann
+///
+/// # Beispiel: eigenes Format registrieren
+/// ```
+/// use asp_cli::{Capabilities, Document, Format, FormatError, FormatRegistry, Node};
+///
+/// /// Eine Zeile = ein String
+/// struct Lines;
+///
+/// impl Format for Lines {
+///     fn name(&self) -> &str { "lines" }
+///     fn extensions(&self) -> &[&str] { &["txt"] }
+///     fn capabilities(&self) -> Capabilities {
+///         Capabilities { nested: false, null: false, datetime: false, ..Capabilities::FULL }
+///     }
+///     fn read(&self, input: &str) -> Result<Document, FormatError> {
+///         Ok(Document::new(Node::array(input.lines().map(Node::string).collect())))
+///     }
+/// }
+///
+/// let mut registry = FormatRegistry::default();
+/// registry.register(Lines);
+///
+/// let json = registry.convert("a\nb\n", "lines", "json")?;
+/// assert_eq!(json, "[\n  \"a\",\n  \"b\"\n]");
+/// # Ok::<(), FormatError>(())
+/// ```
+pub trait Format: Send + Sync {
+    /// Eindeutiger Name (klein geschrieben), z.B. "json"
+    fn name(&self) -> &str;
+
+    /// Weitere Namen, unter denen das Format gefunden wird (z.B. "yml")
+    fn aliases(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Dateiendungen ohne Punkt
+    fn extensions(&self) -> &[&str];
+
+    /// Fähigkeiten des Formats
+    fn capabilities(&self) -> Capabilities;
+
+    /// Liest einen Input-String in das Zwischenmodell
+    fn read(&self, _input: &str) -> Result<Document, FormatError> {
+        Err(FormatError::InvalidFormat(format!("{} kann nicht gelesen werden", self.name())))
+    }
+
+    /// Wie `read`, mit Optionen (z.B. CSV-Dialekt)
+    ///
+    /// Formate ohne Optionen müssen nur `read` implementieren.
+    fn read_with(&self, input: &str, _options: &ConvertOptions) -> Result<Document, FormatError> {
+        self.read(input)
+    }
+
+    /// Wie `write`, mit Optionen
+    fn write_with(&self, document: &Document, _options: &ConvertOptions) -> Result<String, FormatError> {
+        self.write(document)
+    }
+
+    /// Wie `write_with`, sammelt zusätzlich Warnungen zu Stellen, die nicht
+    /// exakt abgebildet werden konnten (z.B. ausgelassene null-Werte)
+    fn write_with_warnings(
+        &self,
+        document: &Document,
+        options: &ConvertOptions,
+        _warnings: &mut Vec<ConversionWarning>,
+    ) -> Result<String, FormatError> {
+        self.write_with(document, options)
+    }
+
+    /// Prüft nur die Syntax - standardmässig über `read_with`, damit gültig
+    /// dasselbe bedeutet wie "lässt sich konvertieren"
+    fn validate(&self, input: &str, options: &ConvertOptions) -> Result<(), FormatError> {
+        self.read_with(input, options).map(|_| ())
+    }
+
+    /// Schreibt ein Dokument als String in diesem Format
+    fn write(&self, _document: &Document) -> Result<String, FormatError> {
+        Err(FormatError::InvalidFormat(format!("{} kann nicht geschrieben werden", self.name())))
+    }
+
+    /// Schreibt eine Eingabe in dasselbe Format neu, ohne Umweg über das
+    /// Zwischenmodell (z.B. TOML samt Kommentaren und Layout)
+    ///
+    /// `None` (Standard), wenn wie bei jeder anderen Konvertierung gelesen
+    /// und geschrieben werden soll.
+    fn rewrite_same(&self, _input: &str, _options: &ConvertOptions) -> Option<Result<String, FormatError>> {
+        None
+    }
+
+    /// Liest die Datensätze einzeln aus einem Stream, ohne die ganze Eingabe
+    /// im Speicher zu halten (siehe `stream::convert_stream`)
+    ///
+    /// `None`, wenn das Format nur als Ganzes gelesen werden kann (Standard).
+    fn read_records<'a>(&self, _input: &'a mut dyn BufRead, _options: &ConvertOptions) -> Option<Records<'a>> {
+        None
+    }
+
+    /// Writer, der die Einträge eines Root-Arrays einzeln in `output` schreibt
+    ///
+    /// `None`, wenn das Format nur ganze Dokumente schreiben kann (Standard).
+    fn record_writer<'a>(
+        &self,
+        _output: &'a mut dyn Write,
+        _options: &ConvertOptions,
+    ) -> Option<Box<dyn RecordWriter + 'a>> {
+        None
+    }
+
+    /// Prüft ob der Name (oder ein Alias) passt, Gross-/Kleinschreibung egal
+    fn matches_name(&self, name: &str) -> bool {
+        self.name().eq_ignore_ascii_case(name)
+            || self.aliases().iter().any(|alias| alias.eq_ignore_ascii_case(name))
+    }
+
+    /// Prüft ob die Dateiendung passt
+    fn matches_extension(&self, ext: &str) -> bool {
+        self.extensions().iter().any(|e| e.eq_ignore_ascii_case(ext))
+    }
+}
+
+/// Sammlung aller verfügbaren Formate
+///
+/// `FormatRegistry::default()` enthält die eingebauten Formate. Später
+/// registrierte Formate haben Vorrang, so kann auch ein eingebautes Format
+/// ersetzt werden.
+pub struct FormatRegistry {
+    formats: Vec<Box<dyn Format>>,
+}
+
+impl Default for FormatRegistry {
+    fn default() -> Self {
+        let mut registry = FormatRegistry::empty();
+        for format in FileFormat::ALL {
+            registry.formats.push(Box::new(*format));
+        }
+        registry
+    }
+}
+
+impl FormatRegistry {
+    /// Registry ohne Formate
+    pub fn empty() -> Self {
+        FormatRegistry { formats: Vec::new() }
+    }
+
+    /// Fügt ein Format hinzu
+    pub fn register(&mut self, format: impl Format + 'static) -> &mut Self {
+        self.formats.push(Box::new(format));
+        self
+    }
+
+    /// Alle Formate in Registrierungsreihenfolge
+    pub fn formats(&self) -> impl Iterator<Item = &dyn Format> {
+        self.formats.iter().map(|f| f.as_ref())
+    }
+
+    /// Sucht ein Format über Name oder Alias
+    pub fn get(&self, name: &str) -> Option<&dyn Format> {
+        self.formats.iter().rev().find(|f| f.matches_name(name)).map(|f| f.as_ref())
+    }
+
+    /// Wie `get`, aber mit Fehlermeldung inkl. aller bekannten Namen
+    pub fn resolve(&self, name: &str) -> Result<&dyn Format, FormatError> {
+        self.get(name).ok_or_else(|| {
+            let known: Vec<&str> = self.formats().map(|f| f.name()).collect();
+            FormatError::InvalidFormat(format!(
+                "Unbekanntes Format: {} (verfügbar: {})",
+                name,
+                known.join(", ")
+            ))
+        })
+    }
+
+    /// Sucht ein Format über die Dateiendung
+    pub fn for_extension(&self, ext: &str) -> Option<&dyn Format> {
+        self.formats.iter().rev().find(|f| f.matches_extension(ext)).map(|f| f.as_ref())
+    }
+
+    /// Sucht ein Format über die Endung eines Pfads
+    pub fn for_path(&self, path: impl AsRef<Path>) -> Option<&dyn Format> {
+        let ext = path.as_ref().extension()?.to_str()?;
+        self.for_extension(ext)
+    }
+
+    /// Konvertiert zwischen zwei registrierten Formaten (über Name oder Alias)
+    pub fn convert(&self, input: &str, from: &str, to: &str) -> Result<String, FormatError> {
+        self.convert_with(input, from, to, &ConvertOptions::default())
+    }
+
+    /// Wie `convert`, mit Optionen für Reader und Writer
+    pub fn convert_with(
+        &self,
+        input: &str,
+        from: &str,
+        to: &str,
+        options: &ConvertOptions,
+    ) -> Result<String, FormatError> {
+        self.convert_with_warnings(input, from, to, options).map(|(output, _)| output)
+    }
+
+    /// Wie `convert_with`, gibt zusätzlich die Warnungen zu verlustbehafteten
+    /// Stellen zurück (siehe `write_reporting`)
+    pub fn convert_with_warnings(
+        &self,
+        input: &str,
+        from: &str,
+        to: &str,
+        options: &ConvertOptions,
+    ) -> Result<(String, Vec<ConversionWarning>), FormatError> {
+        let source = self.resolve(from)?;
+        let target = self.resolve(to)?;
+        crate::warning::convert_reporting(source, target, input, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_lookup() {
+        let registry = FormatRegistry::default();
+        assert_eq!(registry.get("YML").map(|f| f.name()), Some("yaml"));
+        assert_eq!(registry.for_path("dir/data.csv").map(|f| f.name()), Some("csv"));
+        assert!(registry.get("ini").is_none());
+        assert!(!registry.get("toml").unwrap().capabilities().root_array);
+    }
+
+    #[test]
+    fn test_later_registration_wins() {
+        struct ShoutingJson;
+        impl Format for ShoutingJson {
+            fn name(&self) -> &str {
+                "json"
+            }
+            fn extensions(&self) -> &[&str] {
+                &["json"]
+            }
+            fn capabilities(&self) -> Capabilities {
+                Capabilities { read: false, ..Capabilities::FULL }
+            }
+            fn write(&self, document: &Document) -> Result<String, FormatError> {
+                FileFormat::Json.write(document).map(|s| s.to_uppercase())
+            }
+        }
+
+        let mut registry = FormatRegistry::default();
+        registry.register(ShoutingJson);
+        assert_eq!(registry.convert("a = 'x'", "toml", "json").unwrap(), "{\n  \"A\": \"X\"\n}");
+        assert!(registry.convert("{}", "json", "toml").is_err());
+    }
+}
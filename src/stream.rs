@@ -0,0 +1,268 @@
+// Streaming-Konvertierung: Datensatz für Datensatz statt ganzes Dokument
+//
+// Formate mit einer Folge von Datensätzen (z.B. NDJSON) liefern sie über
+// `Format::read_records` einzeln, Zielformate mit `Format::record_writer`
+// schreiben sie einzeln weiter. So bleibt der Speicherbedarf auch bei
+// Millionen Datensätzen konstant.
+
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufWriter, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use crate::document::{Document, Node};
+use crate::error::FormatError;
+use crate::options::ConvertOptions;
+use crate::registry::Format;
+use crate::warning::{collect_dropped_tags, pointer_push, write_reporting, ConversionWarning};
+
+/// Datensätze aus einem Stream, jeweils als Knoten (Eintrag des Root-Arrays)
+pub type Records<'a> = Box<dyn Iterator<Item = Result<Node, FormatError>> + 'a>;
+
+/// Schreibt die Einträge eines Root-Arrays einzeln (siehe `Format::record_writer`)
+pub trait RecordWriter {
+    /// Meldet die Warnungen zu einem Datensatz, noch ohne zu schreiben
+    ///
+    /// Wird vor jedem `write_record` aufgerufen, so bricht der strikte Modus
+    /// ab, bevor der Datensatz in der Ausgabe steht. `path` ist der
+    /// JSON-Pointer des Datensatzes (z.B. "/41").
+    fn check_record(&mut self, _record: &Node, _path: &str, _warnings: &mut Vec<ConversionWarning>) {}
+
+    /// Schreibt einen Datensatz (nach `check_record`)
+    fn write_record(&mut self, record: &Node) -> Result<(), FormatError>;
+
+    /// Schliesst die Ausgabe ab (z.B. `]` bei JSON) und leert den Puffer
+    fn finish(self: Box<Self>) -> Result<(), FormatError>;
+
+    /// Braucht der Writer vor dem Schreiben alle Datensätze (z.B. CSV für die
+    /// Spalten)? Dann liest `convert_stream_two_pass` die Eingabe zweimal.
+    fn wants_scan(&self) -> bool {
+        false
+    }
+
+    /// Erster Durchgang: Datensatz ansehen, ohne zu schreiben
+    fn scan_record(&mut self, _record: &Node) {}
+}
+
+/// Fehler beim Schreiben in den Ausgabe-Stream
+pub(crate) fn write_error(error: std::io::Error) -> FormatError {
+    FormatError::IoError(format!("Fehler beim Schreiben der Ausgabe: {}", error))
+}
+
+/// Konvertiert von `input` nach `output`, möglichst Datensatz für Datensatz
+///
+/// Liefert `source` keine Datensätze (`read_records`), wird die Eingabe wie
+/// bei `convert_reporting` komplett gelesen. Ohne `record_writer` im Ziel
+/// werden die Datensätze gesammelt und als ein Dokument geschrieben.
+/// `sort_keys` und `strict` gelten pro Datensatz, im strikten Modus bricht
+/// schon die erste Warnung ab.
+///
+/// # Beispiel
+/// ```
+/// use asp_cli::{stream::convert_stream, ConvertOptions, FileFormat};
+///
+/// let mut input = "{\"a\": 1}\n{\"a\": 2}\n".as_bytes();
+/// let mut output = Vec::new();
+/// convert_stream(&FileFormat::Ndjson, &FileFormat::Csv, &mut input, &mut output, &ConvertOptions::default())?;
+/// assert_eq!(String::from_utf8(output).unwrap(), "a\n1\n2\n");
+/// # Ok::<(), asp_cli::FormatError>(())
+/// ```
+pub fn convert_stream(
+    source: &dyn Format,
+    target: &dyn Format,
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+    options: &ConvertOptions,
+) -> Result<Vec<ConversionWarning>, FormatError> {
+    let Some(records) = source.read_records(input, options) else {
+        let mut content = String::new();
+        input
+            .read_to_string(&mut content)
+            .map_err(|e| FormatError::IoError(format!("Fehler beim Lesen der Eingabe: {}", e)))?;
+        let (result, warnings) = crate::warning::convert_reporting(source, target, &content, options)?;
+        output.write_all(result.as_bytes()).map_err(write_error)?;
+        return Ok(warnings);
+    };
+
+    convert_records(records, target, output, options)
+}
+
+/// Wie `convert_stream`, für Eingaben, die sich zurückspulen lassen (Dateien)
+///
+/// Will der Writer vorher alle Datensätze sehen (`RecordWriter::wants_scan`),
+/// wird die Eingabe zweimal gelesen. So bekommt z.B. CSV die Spalten aller
+/// Datensätze, genau wie bei der Konvertierung als ganzes Dokument, und der
+/// Speicherbedarf bleibt trotzdem konstant.
+pub fn convert_stream_two_pass<R: BufRead + Seek>(
+    source: &dyn Format,
+    target: &dyn Format,
+    input: &mut R,
+    output: &mut dyn Write,
+    options: &ConvertOptions,
+) -> Result<Vec<ConversionWarning>, FormatError> {
+    let Some(records) = source.read_records(input, options) else {
+        return convert_stream(source, target, input, output, options);
+    };
+    let Some(mut writer) = target.record_writer(output, options).filter(|w| w.wants_scan()) else {
+        return convert_records(records, target, output, options);
+    };
+
+    for record in records {
+        let mut record = record?;
+        if options.sort_keys {
+            record.sort_keys();
+        }
+        writer.scan_record(&record);
+    }
+    input
+        .rewind()
+        .map_err(|e| FormatError::IoError(format!("Fehler beim Zurückspulen der Eingabe: {}", e)))?;
+
+    let Some(records) = source.read_records(input, options) else {
+        return Err(FormatError::UnknownError("Eingabe liefert im zweiten Durchgang keine Datensätze".to_string()));
+    };
+    write_records(records, writer, target, options)
+}
+
+/// Wie `convert_stream`, für Eingaben, die sich nicht zurückspulen lassen
+///
+/// Will der Writer vorher alle Datensätze sehen (`RecordWriter::wants_scan`),
+/// werden sie dafür im Speicher gesammelt - die CSV-Spalten sind dann
+/// dieselben wie bei der Konvertierung als ganzes Dokument.
+pub fn convert_stream_buffered(
+    source: &dyn Format,
+    target: &dyn Format,
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+    options: &ConvertOptions,
+) -> Result<Vec<ConversionWarning>, FormatError> {
+    let Some(records) = source.read_records(input, options) else {
+        return convert_stream(source, target, input, output, options);
+    };
+    let Some(mut writer) = target.record_writer(output, options).filter(|w| w.wants_scan()) else {
+        return convert_records(records, target, output, options);
+    };
+
+    let mut buffered = Vec::new();
+    for record in records {
+        let mut record = record?;
+        if options.sort_keys {
+            record.sort_keys();
+        }
+        writer.scan_record(&record);
+        buffered.push(record);
+    }
+    write_records(Box::new(buffered.into_iter().map(Ok)), writer, target, options)
+}
+
+/// Schreibt Datensätze über den `record_writer` des Ziels bzw. gesammelt als
+/// ein Dokument, wenn das Ziel keinen hat
+fn convert_records(
+    records: Records<'_>,
+    target: &dyn Format,
+    output: &mut dyn Write,
+    options: &ConvertOptions,
+) -> Result<Vec<ConversionWarning>, FormatError> {
+    let Some(writer) = target.record_writer(output, options) else {
+        let document = Document::new(Node::array(records.collect::<Result<Vec<_>, _>>()?));
+        let (result, warnings) = write_reporting(target, &document, options)?;
+        output.write_all(result.as_bytes()).map_err(write_error)?;
+        return Ok(warnings);
+    };
+    write_records(records, writer, target, options)
+}
+
+/// Schreibt alle Datensätze über `writer` (siehe `convert_stream`)
+fn write_records(
+    records: Records<'_>,
+    mut writer: Box<dyn RecordWriter + '_>,
+    target: &dyn Format,
+    options: &ConvertOptions,
+) -> Result<Vec<ConversionWarning>, FormatError> {
+    let mut warnings = Vec::new();
+    for (i, record) in records.enumerate() {
+        let mut record = record?;
+        if options.sort_keys {
+            record.sort_keys();
+        }
+        let path = pointer_push("", &i.to_string());
+        let before = warnings.len();
+        writer.check_record(&record, &path, &mut warnings);
+        if !target.capabilities().tags {
+            collect_dropped_tags(&record, &path, &mut warnings);
+        }
+        if options.strict && warnings.len() > before {
+            return Err(FormatError::SerializationError(format!(
+                "Strikter Modus: Warnung bei der Konvertierung nach {}\n  - {}",
+                target.name(),
+                warnings[before]
+            )));
+        }
+        writer.write_record(&record)?;
+    }
+    writer.finish()?;
+    Ok(warnings)
+}
+
+// ============================================================================
+// AUSGABEDATEIEN
+// ============================================================================
+
+/// Ausgabedatei, die erst nach erfolgreichem Schreiben ersetzt wird
+///
+/// Geschrieben wird in eine temporäre Datei im selben Ordner, `commit`
+/// benennt sie um. Ohne `commit` (z.B. nach einem Parse-Fehler mitten im
+/// Stream) wird sie beim Drop gelöscht und die alte Datei bleibt unverändert.
+pub struct PendingFile {
+    target: PathBuf,
+    temp: PathBuf,
+    /// `None` nach `commit`
+    file: Option<BufWriter<File>>,
+}
+
+impl PendingFile {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        // Über einen Symlink wird das Ziel ersetzt, nicht der Link
+        let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let name = target.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        let temp = target.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+        let file = File::create(&temp)?;
+        if let Ok(metadata) = fs::metadata(&target) {
+            // Rechte der bisherigen Datei übernehmen, Fehler sind hier egal
+            let _ = fs::set_permissions(&temp, metadata.permissions());
+        }
+        Ok(PendingFile { target, temp, file: Some(BufWriter::new(file)) })
+    }
+
+    /// Puffer leeren und die Zieldatei ersetzen
+    pub fn commit(mut self) -> io::Result<()> {
+        if let Some(file) = self.file.take() {
+            file.into_inner().map_err(|e| e.into_error())?;
+        }
+        fs::rename(&self.temp, &self.target)
+    }
+}
+
+impl Write for PendingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.file {
+            Some(file) => file.write(buf),
+            None => Err(io::Error::other("Datei ist bereits abgeschlossen")),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for PendingFile {
+    fn drop(&mut self) {
+        // Nach erfolgreichem Rename gibt es die temporäre Datei nicht mehr
+        drop(self.file.take());
+        let _ = fs::remove_file(&self.temp);
+    }
+}
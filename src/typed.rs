@@ -0,0 +1,181 @@
+// Typisierte API: Dateien direkt in eigene Structs lesen bzw. daraus schreiben
+//
+// Statt erst `serde_json::Value` zu erzeugen und das dann zu deserialisieren,
+// wählt `from_str` den Reader über das `FileFormat`. So lädt ein Aufruf eine
+// Konfiguration, egal ob sie in YAML, TOML oder JSON geschrieben ist.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::FormatError;
+use crate::format::FileFormat;
+use crate::formats::csv::{deserialize_csv, CsvOptions, PSV, TSV};
+use crate::formats::json::{read_json_with, JsonNode};
+use crate::options::ConvertOptions;
+use crate::registry::Format;
+use crate::warning::write_reporting;
+
+/// Liest `input` im Format `format` direkt in einen eigenen Typ
+///
+/// CSV, TSV und PSV liefern eine Zeile pro Eintrag, `T` ist dort also z.B.
+/// `Vec<Zeile>`.
+///
+/// # Beispiel
+/// ```
+/// use asp_cli::{from_str, FileFormat};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     name: String,
+///     port: u16,
+/// }
+///
+/// let yaml: Config = from_str("name: app\nport: 8080\n", FileFormat::Yaml)?;
+/// let toml: Config = from_str("name = \"app\"\nport = 8080\n", FileFormat::Toml)?;
+/// assert_eq!((yaml.port, toml.name.as_str()), (8080, "app"));
+/// # Ok::<(), asp_cli::FormatError>(())
+/// ```
+pub fn from_str<T: DeserializeOwned>(input: &str, format: FileFormat) -> Result<T, FormatError> {
+    from_str_with(input, format, &ConvertOptions::default())
+}
+
+/// Wie `from_str`, mit Optionen für den Reader (z.B. CSV-Dialekt)
+///
+/// Datetimes kommen als Strings an, mit `JsonOptions::datetime` auch als
+/// Unix-Zeitstempel.
+pub fn from_str_with<T: DeserializeOwned>(
+    input: &str,
+    format: FileFormat,
+    options: &ConvertOptions,
+) -> Result<T, FormatError> {
+    // CSV ohne Zwischenmodell: der Ziel-Typ entscheidet, ob "007" eine Zahl
+    // oder ein String ist
+    let csv_options = match format {
+        FileFormat::Csv => Some(options.csv.clone()),
+        FileFormat::Tsv => Some(CsvOptions { delimiter: Some(TSV.delimiter), ..options.csv.clone() }),
+        FileFormat::Psv => Some(CsvOptions { delimiter: Some(PSV.delimiter), ..options.csv.clone() }),
+        _ => None,
+    };
+    if let Some(csv_options) = csv_options {
+        return deserialize_csv(input, &csv_options);
+    }
+
+    // Über JSON-Text statt `serde_json::Value`, so bleiben grosse Zahlen exakt
+    let document = format.read_with(input, options)?;
+    let json = serde_json::to_string(&JsonNode::new(&document.root, &options.json))
+        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Formatieren von JSON: {}", e)))?;
+    serde_json::from_str(&json).map_err(|e| {
+        // Die Position im JSON-Zwischentext hilft nicht weiter
+        let message = e.to_string();
+        let suffix = format!(" at line {} column {}", e.line(), e.column());
+        let message = message.strip_suffix(&suffix).unwrap_or(&message);
+        FormatError::parse(format.as_str(), format!("passt nicht zum erwarteten Typ: {}", message), input, None)
+    })
+}
+
+/// Schreibt einen eigenen Typ im Format `format`
+///
+/// Für CSV muss `value` eine Liste von Structs bzw. Maps sein. `options`
+/// gelten wie bei `convert_with` (z.B. `sort_keys`, im strikten Modus bricht
+/// jede Warnung ab).
+///
+/// # Beispiel
+/// ```
+/// use asp_cli::{to_string, ConvertOptions, FileFormat};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Row {
+///     id: u32,
+///     name: &'static str,
+/// }
+///
+/// let rows = vec![Row { id: 1, name: "a" }, Row { id: 2, name: "b" }];
+/// let csv = to_string(&rows, FileFormat::Csv, &ConvertOptions::default())?;
+/// assert_eq!(csv, "id,name\n1,a\n2,b\n");
+/// # Ok::<(), asp_cli::FormatError>(())
+/// ```
+pub fn to_string<T: Serialize + ?Sized>(
+    value: &T,
+    format: FileFormat,
+    options: &ConvertOptions,
+) -> Result<String, FormatError> {
+    let json = serde_json::to_string(value)
+        .map_err(|e| FormatError::SerializationError(format!("Fehler beim Serialisieren: {}", e)))?;
+    let document = read_json_with(&json, &options.json)?;
+    write_reporting(&format, &document, options).map(|(output, _)| output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Row {
+        zip: String,
+        count: Option<u32>,
+        active: bool,
+    }
+
+    #[test]
+    fn test_same_struct_from_every_format() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Config {
+            id: u64,
+            created: String,
+            tags: Vec<String>,
+        }
+
+        let inputs = [
+            (FileFormat::Json, r#"{"id": 42, "created": "2024-01-15T10:30:00Z", "tags": ["a"]}"#),
+            (FileFormat::Yaml, "id: 42\ncreated: 2024-01-15T10:30:00Z\ntags: [a]\n"),
+            (FileFormat::Toml, "id = 42\ncreated = 2024-01-15T10:30:00Z\ntags = [\"a\"]\n"),
+        ];
+        for (format, input) in inputs {
+            let config: Config = from_str(input, format).unwrap_or_else(|e| panic!("{}: {}", format, e));
+            assert_eq!(config.id, 42, "{}", format);
+            assert_eq!(config.created, "2024-01-15T10:30:00Z", "{}", format);
+        }
+
+        // grosse Zahlen bleiben verlustfrei bis in den Ziel-Typ
+        let big: u128 = from_str("123456789012345678901234", FileFormat::Json).unwrap();
+        assert_eq!(big, 123456789012345678901234);
+    }
+
+    #[test]
+    fn test_buffered_serde_types_get_plain_numbers() {
+        // serde puffert bei flatten/untagged - mit `arbitrary_precision` kämen
+        // Zahlen dort als Map an
+        #[derive(Deserialize)]
+        struct Prices {
+            #[serde(flatten)]
+            items: std::collections::HashMap<String, f64>,
+        }
+        #[derive(Debug, PartialEq, Deserialize)]
+        #[serde(untagged)]
+        enum Cell {
+            F(f64),
+            S(String),
+        }
+
+        let prices: Prices = from_str(r#"{"tea": 2.5}"#, FileFormat::Json).unwrap();
+        assert_eq!(prices.items["tea"], 2.5);
+        assert_eq!(serde_json::from_str::<Cell>("1.5").unwrap(), Cell::F(1.5));
+        assert_eq!(from_str::<Cell>("1.5", FileFormat::Json).unwrap(), Cell::F(1.5));
+    }
+
+    #[test]
+    fn test_csv_rows_are_typed_by_target() {
+        let rows: Vec<Row> = from_str("zip;count;active\n01234;3;true\n08150;;FALSE\n", FileFormat::Csv).unwrap();
+        assert_eq!(rows[0], Row { zip: "01234".to_string(), count: Some(3), active: true });
+        assert_eq!(rows[1].count, None);
+
+        let csv = to_string(&rows, FileFormat::Csv, &ConvertOptions::default()).unwrap();
+        assert_eq!(from_str::<Vec<Row>>(&csv, FileFormat::Csv).unwrap(), rows);
+
+        let err = from_str::<Vec<Row>>("zip,count,active\n1,2,true\n1,x,true\n", FileFormat::Csv).unwrap_err();
+        assert_eq!(err.span().map(|s| s.line), Some(3));
+    }
+}
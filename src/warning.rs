@@ -0,0 +1,194 @@
+// Warnungen zu verlustbehafteten Konvertierungen
+//
+// Writer melden Stellen, an denen Daten nicht exakt abgebildet werden
+// konnten, statt sie stillschweigend zu verändern.
+
+use std::borrow::Cow;
+
+use crate::document::{Document, Node, Value};
+use crate::error::FormatError;
+use crate::options::ConvertOptions;
+use crate::registry::Format;
+
+/// Art der Warnung
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    /// null-Wert wurde ausgelassen (Format kennt kein null)
+    NullOmitted,
+    /// Root wurde in ein künstliches Element verpackt (z.B. "data" in TOML)
+    RootWrapped,
+    /// Verschachtelter Wert als JSON-String in einer CSV-Zelle
+    NestedInCell,
+    /// YAML-Tag (z.B. "!secret") geht im Zielformat verloren
+    TagDropped,
+    /// Zahl ausserhalb des Wertebereichs bzw. der Genauigkeit des Zielformats
+    /// (als String geschrieben oder gerundet)
+    NumberOutOfRange,
+    /// Spalte fehlt im CSV-Header (beim Streamen stehen die Spalten nach dem
+    /// ersten Datensatz fest)
+    ColumnDropped,
+    /// Zwei Keys ergeben beim Flattenen dieselbe CSV-Spalte (z.B. `a_b` und
+    /// `a.b`), der spätere Wert gewinnt
+    ColumnCollision,
+}
+
+impl WarningKind {
+    /// Kurzname für Ausgaben, z.B. "null-omitted"
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarningKind::NullOmitted => "null-omitted",
+            WarningKind::RootWrapped => "root-wrapped",
+            WarningKind::NestedInCell => "nested-in-cell",
+            WarningKind::TagDropped => "tag-dropped",
+            WarningKind::NumberOutOfRange => "number-out-of-range",
+            WarningKind::ColumnDropped => "column-dropped",
+            WarningKind::ColumnCollision => "column-collision",
+        }
+    }
+}
+
+/// Eine Warnung mit Position im Dokument
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionWarning {
+    pub kind: WarningKind,
+    /// JSON-Pointer (RFC 6901) auf die betroffene Stelle, z.B. "/server/port"
+    pub path: String,
+    pub message: String,
+}
+
+impl ConversionWarning {
+    pub fn new(kind: WarningKind, path: impl Into<String>, message: impl Into<String>) -> Self {
+        ConversionWarning { kind, path: path.into(), message: message.into() }
+    }
+}
+
+impl std::fmt::Display for ConversionWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{} bei {}", self.message, self.path)
+        }
+    }
+}
+
+// ============================================================================
+// SCHREIBEN MIT BERICHT
+// ============================================================================
+
+/// Schreibt das Dokument und gibt die Ausgabe samt allen Warnungen zurück
+///
+/// Ergänzt die Warnungen des Writers um formatunabhängige Verluste (Tags in
+/// Formaten ohne Tags). Mit `options.strict` wird jede Warnung zum Fehler,
+/// mit `options.sort_keys` werden die Keys vor dem Schreiben sortiert.
+pub fn write_reporting(
+    format: &dyn Format,
+    document: &Document,
+    options: &ConvertOptions,
+) -> Result<(String, Vec<ConversionWarning>), FormatError> {
+    let document = if options.sort_keys {
+        let mut sorted = document.clone();
+        sorted.sort_keys();
+        Cow::Owned(sorted)
+    } else {
+        Cow::Borrowed(document)
+    };
+
+    let mut warnings = Vec::new();
+    let output = format.write_with_warnings(&document, options, &mut warnings)?;
+
+    if !format.capabilities().tags {
+        collect_dropped_tags(&document.root, "", &mut warnings);
+    }
+
+    if options.strict && !warnings.is_empty() {
+        let list: Vec<String> = warnings.iter().map(|w| format!("  - {}", w)).collect();
+        return Err(FormatError::SerializationError(format!(
+            "Strikter Modus: {} Warnung(en) bei der Konvertierung nach {}\n{}",
+            warnings.len(),
+            format.name(),
+            list.join("\n")
+        )));
+    }
+
+    Ok((output, warnings))
+}
+
+/// Liest `input` und schreibt es im Zielformat, samt Warnungen
+///
+/// Bietet das Format für Quelle = Ziel eine eigene Umwandlung an
+/// (`Format::rewrite_same`, z.B. TOML → TOML samt Kommentaren und Layout),
+/// wird das Zwischenmodell übersprungen.
+pub fn convert_reporting(
+    source: &dyn Format,
+    target: &dyn Format,
+    input: &str,
+    options: &ConvertOptions,
+) -> Result<(String, Vec<ConversionWarning>), FormatError> {
+    if source.name() == target.name()
+        && let Some(result) = source.rewrite_same(input, options)
+    {
+        return result.map(|output| (output, Vec::new()));
+    }
+    // Unaufgelöste Merge-Keys wären in anderen Formaten normale "<<"-Keys
+    let read_options = if options.yaml.keep_aliases && !target.capabilities().aliases {
+        let mut read_options = options.clone();
+        read_options.yaml.keep_aliases = false;
+        Cow::Owned(read_options)
+    } else {
+        Cow::Borrowed(options)
+    };
+    write_reporting(target, &source.read_with(input, &read_options)?, options)
+}
+
+/// Meldet alle Tags im Baum (Reihenfolge wie im Dokument)
+pub(crate) fn collect_dropped_tags(node: &Node, path: &str, warnings: &mut Vec<ConversionWarning>) {
+    if let Some(tag) = &node.tag {
+        warnings.push(ConversionWarning::new(
+            WarningKind::TagDropped,
+            path,
+            format!("YAML-Tag {} entfernt", tag),
+        ));
+    }
+    match &node.value {
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                collect_dropped_tags(item, &pointer_push(path, &i.to_string()), warnings);
+            }
+        }
+        Value::Map(map) => {
+            for (key, value) in map.iter() {
+                collect_dropped_tags(value, &pointer_push(path, &key.to_key_string()), warnings);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Hängt ein Segment an einen JSON-Pointer an ("~" → "~0", "/" → "~1")
+pub(crate) fn pointer_push(path: &str, segment: &str) -> String {
+    format!("{}/{}", path, segment.replace('~', "~0").replace('/', "~1"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::FileFormat;
+
+    #[test]
+    fn test_reports_tags_and_strict() {
+        let doc = FileFormat::Yaml.read("password: !secret abc\nlist: [1, 2]\n").unwrap();
+
+        let (_, warnings) = write_reporting(&FileFormat::Json, &doc, &ConvertOptions::default()).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::TagDropped);
+        assert_eq!(warnings[0].path, "/password");
+
+        // YAML behält Tags
+        let (_, warnings) = write_reporting(&FileFormat::Yaml, &doc, &ConvertOptions::default()).unwrap();
+        assert!(warnings.is_empty());
+
+        let strict = ConvertOptions { strict: true, ..ConvertOptions::default() };
+        assert!(write_reporting(&FileFormat::Json, &doc, &strict).is_err());
+    }
+}